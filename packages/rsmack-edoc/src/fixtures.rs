@@ -0,0 +1,3 @@
+//! Constants resolved by the `edoc` tests, declared with `from = fixtures`
+#![allow(dead_code)]
+const VERSION: &str = "1.0";
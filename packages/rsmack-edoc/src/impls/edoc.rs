@@ -1,3 +1,5 @@
+// The darling derives of this module expand to a redundant `continue`, out of reach of an `allow` on the derived structs
+#![allow(clippy::needless_continue)]
 //! # Edoc Procedural Macro
 //!
 //! This module implements a procedural macro called `edoc` (documentation macro) that generates
@@ -38,13 +40,18 @@
 //! }
 //! ```
 
-use darling::{ast, FromDeriveInput, FromField, FromMeta, FromVariant};
+use darling::{FromField, FromMeta, FromVariant};
 use proc_macro2::TokenStream;
 use quote::*;
-use rsmack_utils::{fs::package_src_folder, megamac::ExecEnv};
+use rsmack_utils::{
+    attrs::{strip_struct_attrs, Member, StrippedStruct},
+    fs::package_src_folder,
+    megamac::ExecEnv,
+};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{self, BufRead};
+use syn::spanned::Spanned;
 use syn::*;
 
 /// Represents the expression to evaluate for documentation concatenation
@@ -81,17 +88,6 @@ pub struct Args {
     from: syn::Path,
 }
 
-/// Parses the main derive input with `edoc` attributes
-///
-/// Processes the struct/enum definition and extracts all fields/variants
-/// that have `edoc` attributes for documentation generation.
-#[derive(Debug, FromDeriveInput)]
-#[darling(attributes(edoc), forward_attrs(allow, doc, cfg))]
-pub struct DeriveInputArgs {
-    /// The data of the derive input (struct fields or enum variants)
-    data: ast::Data<EdocField, EdocField>,
-}
-
 /// Reads a file line by line and returns an iterator over the lines
 ///
 /// # Arguments
@@ -107,56 +103,6 @@ where
     Ok(io::BufReader::new(file).lines())
 }
 
-/// Removes specified attributes from struct fields and replaces them with new attributes
-///
-/// This function processes each field in a struct, finds attributes with the specified names,
-/// removes them, and replaces them with new attributes generated by the `replace_with` closure.
-///
-/// # Type Parameters
-/// * `R` - Closure type that generates replacement attributes
-///
-/// # Arguments
-/// * `attrs_to_remove` - List of attribute names to remove (e.g., `["edoc"]`)
-/// * `item` - The struct item to process
-/// * `replace_with` - Closure that generates replacement attributes based on field and removal count
-///
-/// # Returns
-/// * `ItemStruct` - The struct with specified attributes replaced
-fn rm_item_fields_attrs<
-    R: Fn(
-        &mut Field,
-        &String,
-        // Removed count
-        usize,
-    ) -> Attribute,
->(
-    attrs_to_remove: Vec<String>,
-    item: &mut ItemStruct,
-    replace_with: R,
-) -> ItemStruct {
-    for attr_to_rm in &attrs_to_remove {
-        let mut removed_count: usize = 0;
-        for field in &mut item.fields {
-            let attr_to_rm_idx = field.attrs.iter().position(|a| match a {
-                Attribute {
-                    meta: Meta::List(MetaList { path, .. }),
-                    ..
-                } => {
-                    let id = path.segments.first().unwrap().ident.clone();
-                    &id.to_string() == attr_to_rm
-                }
-                _ => false,
-            });
-            if let Some(i) = attr_to_rm_idx {
-                let replacement_attr = replace_with(field, attr_to_rm, removed_count);
-                field.attrs[i] = replacement_attr;
-                removed_count += 1;
-            }
-        }
-    }
-    item.clone()
-}
-
 /// Main macro execution function
 ///
 /// Processes the struct with `edoc` attributes by:
@@ -171,31 +117,30 @@ fn rm_item_fields_attrs<
 /// * `env` - Macro execution environment for error reporting and utilities
 ///
 /// # Returns
-/// * `TokenStream` - The transformed struct with generated documentation attributes
+/// * `TokenStream` - The transformed struct with generated documentation attributes,
+///   or the errors of the `edoc` field attributes which cannot be parsed
 ///
 /// # Panics
-/// * If macro arguments cannot be parsed
 /// * If unsupported expression types are encountered
 /// * If file reading fails
 /// * If constant resolution fails
 pub fn exec(args: Args, item: ItemStruct, env: ExecEnv) -> TokenStream {
-    let derive_input = syn::parse2::<syn::DeriveInput>(item.to_token_stream()).unwrap();
     let call_site_file_path = call_site_file_path_from_syn_path(args.from);
-    let derive_args = match DeriveInputArgs::from_derive_input(&derive_input) {
-        Ok(args) => args,
-        Err(e) => env
-            .logr
-            .abort_call_site(format!("Failed to parse macro args: {e}")),
+    let StrippedStruct {
+        item: mut edoc_replaced_item,
+        fields: edoc_fields,
+    } = match strip_struct_attrs::<EdocField>(item, &["edoc"]) {
+        Ok(stripped) => stripped,
+        Err(e) => return e.write_errors(),
     };
 
-    let derive_args_data = derive_args.data.take_struct();
-    let mut evaluated_edoc_fields: Vec<String> = vec![];
-    if let Some(fields) = derive_args_data {
+    let mut evaluated_edoc_fields: Vec<(Member, usize, String)> = vec![];
+    if !edoc_fields.is_empty() {
         let mut resolved_consts: HashMap<String, String> = HashMap::new();
-        resolve_consts(call_site_file_path, &mut resolved_consts, &env);
-        for edoc_field in fields {
+        resolve_consts(call_site_file_path.clone(), &mut resolved_consts, &env);
+        for edoc_field in edoc_fields {
             let mut evaluated_elems: Vec<String> = vec![];
-            match edoc_field.concat.expr {
+            match edoc_field.args.concat.expr {
                 Expr::Tuple(tup) => {
                     // Evaluate each element in the tuple expression
                     for elem in tup.elems {
@@ -215,14 +160,21 @@ pub fn exec(args: Args, item: ItemStruct, env: ExecEnv) -> TokenStream {
                             let const_ident = segments.first().unwrap().ident.clone();
                             let const_value = resolved_consts.get(&const_ident.to_string());
                             match const_value {
-                                None => env.logr.emit_error(const_ident.span(),format!("Unresolved const ident {const_ident:?}")),
+                                None => env
+                                    .logr
+                                    .error(const_ident.span(), format!("Unresolved const ident {const_ident:?}"))
+                                    .help(format!("declare it as `const {const_ident}: &str = \"...\";` in `{}`", call_site_file_path.display()))
+                                    .note("only top-level `const` lines with a string, bool or byte string literal are resolved")
+                                    .emit(),
                                 Some(value) => evaluated_elems.push(value.clone())
                             }
 
                         }
-                        _ => env.logr.abort_call_site(
-                            "Unsupported tuple element, only string literal or ident of a const string",
-                        ),
+                        elem => env
+                            .logr
+                            .error(elem.span(), "Unsupported tuple element, only string literal or ident of a const string")
+                            .help("e.g. `#[edoc(expr = (\"Version: \", VERSION))]`")
+                            .abort(),
                     }
                     }
                 }
@@ -232,17 +184,20 @@ pub fn exec(args: Args, item: ItemStruct, env: ExecEnv) -> TokenStream {
             }
             let sep = ""; // No separator - concatenate strings directly
             let evaluated = evaluated_elems.join(sep);
-            evaluated_edoc_fields.push(evaluated);
+            evaluated_edoc_fields.push((edoc_field.member, edoc_field.attr_index, evaluated));
         }
     }
 
-    // Closure to generate replacement #[doc] attributes
-    let field_attr_replacer = |_field: &mut Field, _attr_name: &String, attr_to_rm_idx: usize| {
-        let doc_str = &evaluated_edoc_fields[attr_to_rm_idx];
-        syn::parse_quote! {#[doc = #doc_str]}
-    };
-    let edoc_replaced_item =
-        rm_item_fields_attrs(vec!["edoc".into()], &mut item.clone(), field_attr_replacer);
+    // Generate the #[doc] attributes replacing the stripped #[edoc] ones
+    for (member, attr_index, doc_str) in evaluated_edoc_fields {
+        if let Member::Field(index) = member {
+            if let Some(field) = edoc_replaced_item.fields.iter_mut().nth(index) {
+                field
+                    .attrs
+                    .insert(attr_index, syn::parse_quote! {#[doc = #doc_str]});
+            }
+        }
+    }
     quote! {
         #edoc_replaced_item
     }
@@ -326,3 +281,63 @@ fn resolve_consts(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use rsmack_utils::testing::expand_attr;
+
+    #[test]
+    fn replaces_edoc_by_doc_in_place() {
+        let expansion = expand_attr!(
+            impls,
+            Args,
+            edoc,
+            syn::ItemStruct,
+            quote!(from = fixtures),
+            quote! {
+                struct Config {
+                    #[allow(unused)]
+                    #[edoc(expr = ("Version ", VERSION))]
+                    #[cfg(all())]
+                    version: String,
+                    name: String,
+                }
+            }
+        );
+        assert_eq!(expansion.messages.len(), 0);
+        assert_eq!(
+            expansion.output.unwrap().to_string(),
+            quote! {
+                struct Config {
+                    #[allow(unused)]
+                    #[doc = "Version 1.0"]
+                    #[cfg(all())]
+                    version: String,
+                    name: String,
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn reports_edoc_attribute_errors() {
+        let expansion = expand_attr!(
+            impls,
+            Args,
+            edoc,
+            syn::ItemStruct,
+            quote!(from = fixtures),
+            quote! {
+                struct Config {
+                    #[edoc(exp = ("Version ", VERSION))]
+                    version: String,
+                }
+            }
+        );
+        let output = expansion.output.unwrap().to_string();
+        assert!(output.starts_with(":: core :: compile_error !"));
+        assert!(output.contains("Unknown field: `exp`"));
+    }
+}
@@ -1,3 +1,5 @@
 use rsmack_megamac::*;
+#[cfg(test)]
+mod fixtures;
 mod impls;
 megamac!(kind = Attr, name = edoc, receiver = ItemStruct);
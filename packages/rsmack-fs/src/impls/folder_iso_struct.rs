@@ -46,10 +46,14 @@ pub fn exec(args: Args, item: ItemStruct, env: ExecEnv) -> TokenStream {
     // Ensure we're only working with structs that have named fields
     transformed_item.fields = match transformed_item.fields {
         syn::Fields::Named(fields_named) => syn::Fields::Named(fields_named),
-        _ => env.logr.abort(
-            transformed_item.fields.span(),
-            "Only named struct supported",
-        ),
+        _ => env
+            .logr
+            .error(
+                transformed_item.fields.span(),
+                "Only named struct supported",
+            )
+            .note_at(env.call_site, "required by this attribute")
+            .abort(),
     };
 
     let name = item.ident.clone();
@@ -0,0 +1,10 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use rsmack_utils::megamac::ExecEnv;
+
+#[derive(Debug, FromDeriveInput)]
+pub struct Args {}
+
+pub fn exec(_args: Args, _env: ExecEnv) -> TokenStream {
+    TokenStream::new()
+}
@@ -0,0 +1,16 @@
+use darling::FromMeta;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rsmack_utils::megamac::ExecEnv;
+use syn::{ItemEnum, ItemStruct};
+
+#[derive(Debug, FromMeta)]
+pub struct Args {}
+
+pub fn exec_struct(_args: Args, item: ItemStruct, _env: ExecEnv) -> TokenStream {
+    quote!(#item)
+}
+
+pub fn exec_enum(_args: Args, item: ItemEnum, _env: ExecEnv) -> TokenStream {
+    quote!(#item)
+}
@@ -0,0 +1,11 @@
+use darling::FromMeta;
+use proc_macro2::{Span, TokenStream};
+use rsmack_utils::megamac::ExecEnv;
+
+#[derive(Debug, FromMeta)]
+pub struct Args {}
+
+pub fn exec(_args: Args, env: ExecEnv) -> syn::Result<TokenStream> {
+    env.logr.emit_error(Span::call_site(), "Fails first");
+    Err(syn::Error::new(Span::call_site(), "Always fails"))
+}
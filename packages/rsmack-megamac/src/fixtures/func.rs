@@ -0,0 +1,10 @@
+use darling::FromMeta;
+use proc_macro2::TokenStream;
+use rsmack_utils::megamac::ExecEnv;
+
+#[derive(Debug, FromMeta)]
+pub struct Args {}
+
+pub fn exec(_args: Args, _env: ExecEnv) -> TokenStream {
+    TokenStream::new()
+}
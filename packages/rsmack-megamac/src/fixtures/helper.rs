@@ -0,0 +1,16 @@
+// The darling derive of this module expands to a redundant `continue`, out of reach of an `allow` on the derived struct
+#![allow(clippy::needless_continue)]
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use rsmack_utils::megamac::ExecEnv;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(helper))]
+pub struct Args {
+    #[darling(default)]
+    skip: bool,
+}
+
+pub fn exec(_args: Args, _env: ExecEnv) -> TokenStream {
+    TokenStream::new()
+}
@@ -0,0 +1,12 @@
+//! Implementation modules read by the `megamac` tests, declared with `impls_mod = fixtures`,
+//! compiled with the tests to keep them valid but never called
+#![allow(dead_code)]
+pub mod attr;
+pub mod by_ref;
+pub mod derive;
+pub mod dispatch;
+pub mod fallible;
+pub mod func;
+pub mod helper;
+pub mod opts;
+pub mod sum;
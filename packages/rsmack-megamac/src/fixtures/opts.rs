@@ -0,0 +1,10 @@
+use darling::FromMeta;
+use proc_macro2::TokenStream;
+use rsmack_utils::megamac::ExecEnv;
+
+#[derive(Debug, FromMeta)]
+pub struct Opts {}
+
+pub fn exec(_opts: Opts, _env: ExecEnv) -> TokenStream {
+    TokenStream::new()
+}
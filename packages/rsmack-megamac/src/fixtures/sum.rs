@@ -0,0 +1,25 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use rsmack_utils::megamac::ExecEnv;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Expr, Token,
+};
+
+pub struct Args {
+    terms: Punctuated<Expr, Token![+]>,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            terms: Punctuated::parse_separated_nonempty(input)?,
+        })
+    }
+}
+
+pub fn exec(args: Args, _env: ExecEnv) -> TokenStream {
+    let terms = args.terms.iter();
+    quote!(0 #(+ #terms)*)
+}
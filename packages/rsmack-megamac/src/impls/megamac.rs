@@ -4,25 +4,23 @@
 //! procedural macros (function-like, attribute, derive) with automatic documentation generation
 //! and proper error handling.
 
-use bon::Builder;
-use darling::FromMeta;
+use darling::{util::SpannedValue, FromMeta};
+use proc_macro2::Span;
 use proc_macro2::TokenStream;
 use quote::*;
-use rsmack_utils::{fs::package_src_folder, megamac::ExecEnv};
-use strum::Display;
+use rsmack_utils::{
+    fs::{folder_modules, package_folder, package_src_folder},
+    megamac::{
+        args_doc::FieldDoc,
+        impl_file::{ArgsStructSummary, ExecFnSummary, ImplFileSummary},
+        manifest::MacroManifest,
+        ExecEnv, MacroKind,
+    },
+};
+use std::{path::PathBuf, sync::Arc};
+use syn::spanned::Spanned;
 use syn::*;
 
-/// Represents the kind of procedural macro to generate.
-#[derive(Debug, PartialEq, Display, FromMeta)]
-enum MacroKind {
-    /// Function-like procedural macro (e.g., `my_macro!()`)
-    Func,
-    /// Attribute procedural macro (e.g., `#[my_macro]`)
-    Attr,
-    /// Derive procedural macro (e.g., `#[derive(MyMacro)]`)
-    Derive,
-}
-
 /// Arguments for configuring the megamac macro generation.
 #[derive(Debug, FromMeta)]
 pub struct Args {
@@ -32,26 +30,209 @@ pub struct Args {
     /// Name of the macro to generate as [`Ident`]
     name: Ident,
 
-    /// The receiver type for attribute macros (only used with [`MacroKind::Attr`])
+    /// The receiver type for attribute macros (only used with [`MacroKind::Attr`]),
+    /// either a bare [`syn`] item name (see [`SYN_RECEIVERS`]), a path to a type implementing [`syn::parse::Parse`],
+    /// or a list of bare [`syn`] item names, e.g. `[ItemStruct, ItemEnum]`, dispatched as a [`syn::Item`] (see [`Receiver::Dispatch`])
+    #[darling(default)]
+    receiver: Option<List<Path>>,
+
+    /// The module of the crate root holding the implementation modules, `impls` by default
+    #[darling(default = default_impls_mod)]
+    impls_mod: Ident,
+
+    /// The name of the arguments struct of the implementation module, `Args` by default
+    #[darling(rename = "args", default = default_args)]
+    exec_args_ident: Ident,
+
+    /// The documentation file included at the top of the macro documentation, a path relative to the package folder or `false`.
+    /// Defaults to `docs/<name>.md` if it exists, `README.md` otherwise
+    #[darling(default)]
+    readme: Readme,
+
+    /// Whether to write a JSON manifest of the macro (see [`MacroManifest`]), `false` by default
+    #[darling(default)]
+    manifest: bool,
+
+    /// The parameter set by a single unnamed value of an attribute macro, e.g. `#[name(value)]` for `#[name(primary = value)]`
+    #[darling(default)]
+    primary: Option<Ident>,
+
+    /// Whether to also generate a `<name>_facade!` function-like macro, declaring in a crate re-exporting
+    /// this one a `<name>!` `macro_rules!` forwarding to the function-like macro through `$crate`, `false` by default
+    #[darling(default)]
+    facade: bool,
+
+    /// The name of the derive macro (only used with [`MacroKind::Derive`]), `<name>` in `PascalCase` by default
+    #[darling(default)]
+    derive_name: Option<Ident>,
+
+    /// The helper attributes of the derive macro (only used with [`MacroKind::Derive`]), e.g. `[a, b]`, `[<name>]` by default
+    #[darling(default)]
+    helper_attrs: Option<List<Ident>>,
+
+    /// The error strategy of the implementation, `proc_macro_error2` by default or `syn` (see [`Errors`])
+    #[darling(default)]
+    errors: Errors,
+
+    /// How the input of a function-like macro is parsed (only used with [`MacroKind::Func`]), `Meta` or `Parse` (see [`Input`]).
+    /// Defaults to `Parse` if the implementation file implements [`syn::parse::Parse`] for a `Args` not deriving [`FromMeta`], `Meta` otherwise
     #[darling(default)]
-    receiver: Option<Ident>,
+    input: Option<SpannedValue<Input>>,
+}
+
+/// How the input of a function-like macro is parsed into its arguments
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Input {
+    /// A meta list parsed with [`FromMeta`], e.g. `name!(a = 1, b)`, given as `input = Meta`
+    Meta,
+    /// A custom syntax parsed with the [`syn::parse::Parse`] implementation of `Args`, e.g. `name!(1 + 1 => a)`, given as `input = Parse`.
+    /// `Args` fields are not documented as parameters
+    Parse,
+}
+
+impl FromMeta for Input {
+    fn from_expr(expr: &Expr) -> darling::Result<Self> {
+        match expr {
+            Expr::Path(ExprPath { path, .. }) if path.is_ident("Meta") => Ok(Self::Meta),
+            Expr::Path(ExprPath { path, .. }) if path.is_ident("Parse") => Ok(Self::Parse),
+            _ => Err(darling::Error::unknown_value(&quote!(#expr).to_string()).with_span(expr)),
+        }
+    }
+}
+
+/// The error strategy of a macro implementation
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum Errors {
+    /// `exec` returns a [`TokenStream`], errors are emitted through [`proc_macro_error2`], given as `errors = proc_macro_error2`
+    #[default]
+    ProcMacroError2,
+    /// `exec` returns a [`syn::Result`], errors are converted with [`syn::Error::to_compile_error`]
+    /// (see [`rsmack_utils::exec::exec_syn`]), given as `errors = syn`
+    Syn,
+}
+
+impl FromMeta for Errors {
+    fn from_expr(expr: &Expr) -> darling::Result<Self> {
+        match expr {
+            Expr::Path(ExprPath { path, .. }) if path.is_ident("proc_macro_error2") => {
+                Ok(Self::ProcMacroError2)
+            }
+            Expr::Path(ExprPath { path, .. }) if path.is_ident("syn") => Ok(Self::Syn),
+            _ => Err(darling::Error::unknown_value(&quote!(#expr).to_string()).with_span(expr)),
+        }
+    }
+}
+
+/// A list of values given as an array, e.g. `key = [a, b]`, or as a single value, e.g. `key = a`
+#[derive(Debug)]
+struct List<T>(Vec<T>);
+
+impl<T: FromMeta> FromMeta for List<T> {
+    fn from_expr(expr: &Expr) -> darling::Result<Self> {
+        match expr {
+            Expr::Array(ExprArray { elems, .. }) => {
+                let mut errors = darling::Error::accumulator();
+                let values = elems
+                    .iter()
+                    .filter_map(|elem| {
+                        errors.handle(T::from_expr(elem).map_err(|e| e.with_span(elem)))
+                    })
+                    .collect();
+                errors.finish_with(Self(values))
+            }
+            _ => T::from_expr(expr).map(|value| Self(vec![value])),
+        }
+    }
+}
+
+/// The documentation file included at the top of the generated macro documentation.
+#[derive(Debug, Default)]
+enum Readme {
+    /// `docs/<name>.md` if it exists, the package `README.md` otherwise, if it exists
+    #[default]
+    Lookup,
+    /// A file path relative to the package folder, given as `readme = "path"`
+    Path(LitStr),
+    /// No documentation file, given as `readme = false`
+    Disabled,
+}
+
+impl FromMeta for Readme {
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        match value {
+            Lit::Str(path) => Ok(Self::Path(path.clone())),
+            Lit::Bool(LitBool { value: true, .. }) => Ok(Self::Lookup),
+            Lit::Bool(LitBool { value: false, .. }) => Ok(Self::Disabled),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+    }
 }
 
-impl From<Ident> for MacroKind {
-    /// Converts an [`Ident`] to a [`MacroKind`].
+fn default_impls_mod() -> Ident {
+    format_ident!("impls")
+}
+
+fn default_args() -> Ident {
+    format_ident!("Args")
+}
+
+/// [`syn`] item types which can be used as a bare `receiver` ident
+const SYN_RECEIVERS: &[&str] = &[
+    "Item",
+    "ItemConst",
+    "ItemEnum",
+    "ItemExternCrate",
+    "ItemFn",
+    "ItemForeignMod",
+    "ItemImpl",
+    "ItemMacro",
+    "ItemMod",
+    "ItemStatic",
+    "ItemStruct",
+    "ItemTrait",
+    "ItemTraitAlias",
+    "ItemType",
+    "ItemUnion",
+    "ItemUse",
+];
+
+/// The resolved receiver of an attribute macro
+#[derive(Debug)]
+enum Receiver {
+    /// A single receiver type, parsed and given to `exec`
+    Single(Path),
+    /// Several [`syn`] item types, parsed as a [`syn::Item`] and dispatched:
+    /// - to `exec(Args, Item, ExecEnv)` if the implementation file declares it
+    /// - to `exec_<item>(Args, <ItemX>, ExecEnv)` otherwise, e.g. `exec_struct(Args, ItemStruct, ExecEnv)` for `ItemStruct`
     ///
-    /// # Panics
-    /// Panics if the identifier doesn't match "Attr", "Func", or "Derive".
-    fn from(value: Ident) -> Self {
-        match value.to_string().as_str() {
-            "Attr" => Self::Attr,
-            "Func" => Self::Func,
-            "Derive" => Self::Derive,
-            _ => panic!("Unsupported MacroKind"),
+    /// Other items abort with an error naming the accepted receivers.
+    Dispatch(Vec<Ident>),
+}
+
+impl Receiver {
+    /// The type parsed from the item token stream
+    fn ty(&self) -> Path {
+        match self {
+            Self::Single(receiver) => receiver.clone(),
+            Self::Dispatch(_) => parse_quote!(syn::Item),
         }
     }
 }
 
+/// The [`syn::Item`] variant of a [`syn`] item type, e.g. `Struct` for `ItemStruct`
+fn item_variant(receiver: &Ident) -> Ident {
+    let receiver = receiver.to_string();
+    format_ident!("{}", receiver.strip_prefix("Item").unwrap_or(&receiver))
+}
+
+/// The dispatched implementation function of a [`syn`] item type, e.g. `exec_struct` for `ItemStruct`
+fn dispatch_exec_fn(receiver: &Ident) -> Ident {
+    format_ident!(
+        "exec_{}",
+        stringcase::snake_case(&item_variant(receiver).to_string())
+    )
+}
+
 /// Executes the megamac macro to generate a procedural macro implementation.
 ///
 /// This function takes configuration arguments and an execution environment,
@@ -66,66 +247,91 @@ impl From<Ident> for MacroKind {
 /// A [`TokenStream`] containing the generated procedural macro implementation.
 pub fn exec(args: Args, env: ExecEnv) -> TokenStream {
     let name = args.name.clone();
-    let receiver = args.receiver.clone();
-    let imports = quote! {
-        use proc_macro_error2::*;
+    let (imports, entry_attr, errors_option) = match args.errors {
+        Errors::ProcMacroError2 => (
+            quote!(
+                use proc_macro_error2::*;
+            ),
+            quote!(#[proc_macro_error]),
+            quote!(),
+        ),
+        Errors::Syn => (quote!(), quote!(), quote!(, errors = syn)),
     };
-    let kind = args.kind.to_string();
-    let macro_impl_file_ast = get_macro_impl_file_ast(&args, &env);
-    let fields_doc = get_args_fields_doc(&macro_impl_file_ast, &args, &env);
+    let macro_kind = get_macro_kind(&args, &env);
+    let receiver = get_receiver_ty(&args, macro_kind, &env);
+    let impl_file = get_impl_file_summary(&args.impls_mod, &args.name, &args.exec_args_ident, &env);
+    let dispatch_exec =
+        check_exec_signatures(&impl_file, macro_kind, receiver.as_ref(), &args, &env);
+    let args_struct = get_args_struct(&impl_file, &args, &env);
+    let mut fields_doc = args_struct.fields_doc.clone();
+    let helper_attrs = args.helper_attrs.as_ref().map_or_else(
+        || vec![args.name.clone()],
+        |List(helper_attrs)| helper_attrs.clone(),
+    );
+    if macro_kind == MacroKind::Derive {
+        check_derive_args_struct(args_struct, &args, &env);
+        check_derive_helper_attrs(&impl_file, &helper_attrs, &args, &env);
+        // Forwarded derive input fields are not helper attribute parameters
+        fields_doc.retain(|fd| !DERIVE_INPUT_FORWARDED_FIELDS.contains(&fd.ident.as_str()));
+    }
+    let input = get_input(args_struct, &args, macro_kind);
+    if input == Some(Input::Parse) {
+        // Fields of a custom syntax are not parameters
+        fields_doc.clear();
+    }
 
-    // Format field documentation for inclusion in the generated macro docs
-    let formatted_fields_doc = fields_doc
-        .iter()
-        .map(|fd| {
-            let template_without_ty_qualified_path = format!(
-                "* `{}` - {}\n  + type: [`{}`]",
-                fd.ident,
-                fd.doc.clone().unwrap_or("Not documented".into()),
-                fd.ty.to_token_stream().to_string().replace(' ', ""),
-            );
+    if args.manifest {
+        write_manifest(&args, macro_kind, receiver.as_ref(), &fields_doc, &env);
+    }
 
-            quote! { #[doc = #template_without_ty_qualified_path]}
-        })
-        .collect::<Vec<_>>();
+    check_kind_options(&args, macro_kind, &env);
+    let attr_options = get_attr_options(args_struct, &fields_doc, &args, macro_kind, &env);
+    let dispatch_option = receiver
+        .as_ref()
+        .and_then(|receiver| get_dispatch_option(receiver, dispatch_exec, &args));
 
     let name_str = name.to_string();
-    let kind_str = kind.clone();
-
-    // Generate the main documentation for the macro
-    let doc = quote! {
-        #[doc = include_str!("../README.md")]
-        #[doc = concat!(#name_str, " procedural macro (", #kind_str, ").")]
-        #[doc ="# Parameters"]
-        #(#formatted_fields_doc)*
-    };
+    let doc = get_macro_doc(args_struct, &fields_doc, input, &args, &env);
 
     // Generate the appropriate macro implementation based on the kind
-    let macro_impl = match args.kind.into() {
+    let impls_mod = &args.impls_mod;
+    let args_ident = &args.exec_args_ident;
+    let macro_impl = match macro_kind {
         MacroKind::Derive => {
-            let derive_name = Ident::new(&stringcase::pascal_case(&name_str), name.span());
+            let derive_name = args
+                .derive_name
+                .clone()
+                .unwrap_or_else(|| Ident::new(&stringcase::pascal_case(&name_str), name.span()));
             quote! {
-                #[proc_macro_error]
-                #[proc_macro_derive(#derive_name, attributes(#name))]
+                #entry_attr
+                #[proc_macro_derive(#derive_name, attributes(#(#helper_attrs),*))]
                 pub fn #name(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-                    rsmack_utils::exec::call_derive_impls_with_args!(#name, item)
+                    rsmack_utils::exec::call_derive_proc_macro!(#impls_mod, #args_ident, #name, item #errors_option)
                 }
             }
         }
-        MacroKind::Func => quote! {
-            #[proc_macro_error]
-            #[proc_macro]
-            pub fn #name(args: proc_macro::TokenStream) -> proc_macro::TokenStream {
-                rsmack_utils::exec::call_func_impls_with_args!(#name, args)
+        MacroKind::Func => {
+            let facade = get_facade_impl(&args);
+            let input_option = (input == Some(Input::Parse)).then(|| quote!(, input = Parse));
+            quote! {
+                #entry_attr
+                #[proc_macro]
+                pub fn #name(args: proc_macro::TokenStream) -> proc_macro::TokenStream {
+                    rsmack_utils::exec::call_func_proc_macro!(#impls_mod, #args_ident, #name, args #input_option #errors_option)
+                }
+                #facade
             }
-        },
-        MacroKind::Attr => quote! {
-            #[proc_macro_error]
-            #[proc_macro_attribute]
-            pub fn #name(attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-                rsmack_utils::exec::call_attr_impls_with_args!(#name, syn::#receiver, attr, item)
+        }
+        MacroKind::Attr => {
+            let receiver_ty = receiver.as_ref().map(Receiver::ty);
+            quote! {
+                #entry_attr
+                #[proc_macro_attribute]
+                pub fn #name(attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+                    rsmack_utils::exec::call_attr_proc_macro!(#impls_mod, #args_ident, #name, #receiver_ty, attr, item #attr_options #dispatch_option #errors_option)
+                }
             }
-        },
+        }
     };
 
     quote! {
@@ -135,111 +341,1259 @@ pub fn exec(args: Args, env: ExecEnv) -> TokenStream {
     }
 }
 
-/// Parses and returns the AST of the macro implementation file.
+/// Resolves how the input of a function-like macro is parsed, [`None`] for other kinds (see [`Args::input`]).
+fn get_input(args_struct: &ArgsStructSummary, args: &Args, kind: MacroKind) -> Option<Input> {
+    if kind != MacroKind::Func {
+        return None;
+    }
+    Some(match &args.input {
+        Some(input) => **input,
+        None if args_struct.implements("Parse") && !args_struct.implements("FromMeta") => {
+            Input::Parse
+        }
+        None => Input::Meta,
+    })
+}
+
+/// Parses the `kind` of the macro to generate.
 ///
-/// # Arguments
-/// - `args`: The macro configuration arguments
-/// - `env`: The execution environment
+/// # Aborts
+/// Aborts compilation if the kind is not one of [`MacroKind`] variants.
+fn get_macro_kind(args: &Args, env: &ExecEnv) -> MacroKind {
+    match args.kind.to_string().parse() {
+        Ok(macro_kind) => macro_kind,
+        Err(e) => env.logr.abort(args.kind.span(), e),
+    }
+}
+
+/// Generates the documentation of the macro: its [`Readme`], parameters and examples.
+fn get_macro_doc(
+    args_struct: &ArgsStructSummary,
+    fields_doc: &[FieldDoc],
+    input: Option<Input>,
+    args: &Args,
+    env: &ExecEnv,
+) -> TokenStream {
+    // Format field documentation for inclusion in the generated macro docs
+    let formatted_fields_doc = fields_doc
+        .iter()
+        .map(|fd| {
+            let markdown = fd.to_markdown();
+            quote! { #[doc = #markdown]}
+        })
+        .collect::<Vec<_>>();
+
+    let name_str = args.name.to_string();
+    let kind_str = args.kind.to_string();
+    let examples_doc = get_examples_doc(args_struct, args);
+    let readme_doc = get_readme_doc(args, env);
+    // A custom syntax has no parameters
+    let parameters_doc = (input != Some(Input::Parse)).then(|| quote!(#[doc = "# Parameters"]));
+
+    quote! {
+        #readme_doc
+        #[doc = concat!(#name_str, " procedural macro (", #kind_str, ").")]
+        #parameters_doc
+        #(#formatted_fields_doc)*
+        #examples_doc
+    }
+}
+
+/// Includes files as unused bytes, so that cargo rebuilds the macro crate when they change.
+///
+/// Documentation and example files are already included with [`include_str`], and implementation files are modules of the crate.
+fn track_files(read_paths: &[PathBuf]) -> TokenStream {
+    let read_paths = read_paths.iter().map(|path| path.display().to_string());
+    quote! {
+        #(const _: &[u8] = include_bytes!(#read_paths);)*
+    }
+}
+
+/// Rejects the options given for another macro kind than the one they apply to.
+fn check_kind_options(args: &Args, kind: MacroKind, env: &ExecEnv) {
+    let kind_options: [(&str, Option<Span>, MacroKind); 5] = [
+        (
+            "primary",
+            args.primary.as_ref().map(Ident::span),
+            MacroKind::Attr,
+        ),
+        (
+            "facade",
+            args.facade.then(|| args.name.span()),
+            MacroKind::Func,
+        ),
+        (
+            "derive_name",
+            args.derive_name.as_ref().map(Ident::span),
+            MacroKind::Derive,
+        ),
+        (
+            "helper_attrs",
+            args.helper_attrs.as_ref().map(|List(helper_attrs)| {
+                helper_attrs.first().map_or(args.name.span(), Ident::span)
+            }),
+            MacroKind::Derive,
+        ),
+        (
+            "input",
+            args.input.as_ref().map(SpannedValue::span),
+            MacroKind::Func,
+        ),
+    ];
+    for (option, span, option_kind) in kind_options {
+        if let Some(span) = span.filter(|_| option_kind != kind) {
+            env.logr.emit_error(
+                span,
+                format!("`{option}` is only used with `{option_kind}` macros, not `{kind}`"),
+            );
+        }
+    }
+}
+
+/// Generates the `<name>_facade!` macro of a function-like macro declared with `facade = true`,
+/// expanding to [`rsmack_utils::megamac::facade::macro_rules_facade`].
+fn get_facade_impl(args: &Args) -> Option<TokenStream> {
+    if !args.facade {
+        return None;
+    }
+    let name = &args.name;
+    let name_str = name.to_string();
+    let facade_name = format_ident!("{name}_facade");
+    let crate_name = std::env::var("CARGO_CRATE_NAME").unwrap_or_default();
+    let doc = format!(
+        "Declares a `{name}!` `macro_rules!` forwarding to [`{name}!`]({name}) through `$crate`, \
+        to re-export it from another crate with hygienic paths.\n\n\
+        Invoked at the root of the re-exporting crate, given the path of the re-export of this crate, \
+        `{crate_name}` by default:\n\
+        ```ignore\n\
+        #[doc(hidden)]\n\
+        pub use {crate_name};\n\
+        {crate_name}::{facade_name}!();\n\
+        ```"
+    );
+    Some(quote! {
+        #[doc = #doc]
+        #[proc_macro]
+        pub fn #facade_name(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+            rsmack_utils::megamac::facade::macro_rules_facade(#name_str, env!("CARGO_CRATE_NAME"), input.into())
+                .unwrap_or_else(syn::Error::into_compile_error)
+                .into()
+        }
+    })
+}
+
+/// Builds the trailing options of [`rsmack_utils::exec::call_attr_proc_macro`]:
+/// `bare = Default::default` if the arguments struct implements [`Default`], and `primary = <parameter>` if given.
+///
+/// # Errors
+/// Emits an error if `primary` is not a parameter, and a warning if it is given for a non attribute macro.
+fn get_attr_options(
+    args_struct: &ArgsStructSummary,
+    fields_doc: &[FieldDoc],
+    args: &Args,
+    kind: MacroKind,
+    env: &ExecEnv,
+) -> TokenStream {
+    if kind != MacroKind::Attr {
+        return quote!();
+    }
+    let bare = args_struct
+        .implements("Default")
+        .then(|| quote!(, bare = Default::default));
+    let primary = args.primary.as_ref().map(|primary| {
+        if !fields_doc.iter().any(|fd| *primary == fd.name) {
+            env.logr.emit_error(
+                primary.span(),
+                format!(
+                    "Unknown `primary` parameter `{primary}`, expected one of {}",
+                    fields_doc
+                        .iter()
+                        .map(|fd| format!("`{}`", fd.name))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            );
+        }
+        quote!(, primary = #primary)
+    });
+    quote!(#bare #primary)
+}
+
+/// Builds the `exec` option of [`rsmack_utils::exec::call_attr_proc_macro`] for a [`Receiver::Dispatch`]:
+/// a closure matching the parsed [`syn::Item`] against the accepted variants, aborting on other items.
+///
+/// `dispatch_exec` tells whether the accepted items are given to a single `exec(Args, Item, ExecEnv)`
+/// rather than to `exec_<item>` functions.
+fn get_dispatch_option(
+    receiver: &Receiver,
+    dispatch_exec: bool,
+    args: &Args,
+) -> Option<TokenStream> {
+    let Receiver::Dispatch(receivers) = receiver else {
+        return None;
+    };
+    let impls_mod = &args.impls_mod;
+    let name = &args.name;
+    let arms = receivers.iter().map(|receiver| {
+        let variant = item_variant(receiver);
+        if dispatch_exec {
+            quote!(item @ syn::Item::#variant(_) => crate::#impls_mod::#name::exec(args, item, env))
+        } else {
+            let exec_fn = dispatch_exec_fn(receiver);
+            quote!(syn::Item::#variant(item) => crate::#impls_mod::#name::#exec_fn(args, item, env))
+        }
+    });
+    let unsupported = format!(
+        "Unsupported item for `{name}`, expected one of {}",
+        fmt_receivers(receivers)
+    );
+    let unsupported_arm = match args.errors {
+        Errors::ProcMacroError2 => {
+            quote!(item => env.logr.abort(syn::spanned::Spanned::span(&item), #unsupported))
+        }
+        Errors::Syn => {
+            quote!(item => Err(env.logr.syn_error(syn::spanned::Spanned::span(&item), #unsupported)))
+        }
+    };
+    Some(quote! {
+        , exec = |args, item: syn::Item, env: rsmack_utils::megamac::ExecEnv| match item {
+            #(#arms,)*
+            #unsupported_arm,
+        }
+    })
+}
+
+/// Writes the JSON manifest of the macro to [`MacroManifest::path`].
+///
+/// # Errors
+/// Emits an error if the manifest can not be written.
+fn write_manifest(
+    args: &Args,
+    kind: MacroKind,
+    receiver: Option<&Receiver>,
+    fields_doc: &[FieldDoc],
+    env: &ExecEnv,
+) {
+    let receivers = match receiver {
+        Some(Receiver::Single(receiver)) => {
+            vec![receiver.to_token_stream().to_string().replace(' ', "")]
+        }
+        Some(Receiver::Dispatch(receivers)) => receivers
+            .iter()
+            .map(|receiver| format!("syn::{receiver}"))
+            .collect(),
+        None => vec![],
+    };
+    let manifest = MacroManifest::builder()
+        .name(args.name.to_string())
+        .kind(kind.to_string())
+        .receivers(receivers)
+        .parameters(fields_doc.iter().map(Into::into).collect())
+        .build();
+    if let Err(e) = manifest.write() {
+        env.logr.emit_error(
+            args.name.span(),
+            format!(
+                "Failed to write manifest {}: {e}",
+                manifest.path().display()
+            ),
+        );
+    }
+}
+
+/// Splits the `megamac` input into one declaration per macro to generate.
+///
+/// The input is either:
+/// - a single declaration, e.g. `kind = Func, name = a`
+/// - a list of bracketed declarations, e.g. `[kind = Attr, name = a, receiver = ItemStruct], [kind = Func, name = b]`
+/// - `scan`, to declare every module of the implementations folder exposing `Args` and `exec` (see [`scan_declarations`]),
+///   optionally configured with the `impls_mod` and `args` keys, e.g. `scan(impls_mod = macros)`
+///
+/// The declarations are returned along with the tokens tracking the scanned folder, empty without `scan`.
+///
+/// # Aborts
+/// Aborts compilation if a list of declarations is malformed.
+pub fn declarations(input: TokenStream, env: &ExecEnv) -> (Vec<TokenStream>, TokenStream) {
+    let first_token = input.clone().into_iter().next();
+    match first_token {
+        Some(proc_macro2::TokenTree::Group(group))
+            if group.delimiter() == proc_macro2::Delimiter::Bracket =>
+        {
+            let parser = |input: parse::ParseStream| {
+                let mut declarations = vec![];
+                while !input.is_empty() {
+                    let declaration;
+                    bracketed!(declaration in input);
+                    declarations.push(declaration.parse::<TokenStream>()?);
+                    if !input.is_empty() {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                Ok(declarations)
+            };
+            match parse::Parser::parse2(parser, input) {
+                Ok(declarations) => (declarations, TokenStream::new()),
+                Err(e) => env
+                    .logr
+                    .abort(e.span(), format!("Invalid list of macro declarations: {e}")),
+            }
+        }
+        Some(proc_macro2::TokenTree::Ident(ident)) if ident == "scan" => {
+            let scan_args = match parse2::<Meta>(input).map_err(darling::Error::from) {
+                Ok(Meta::Path(_)) => Ok(ScanArgs::default()),
+                meta => meta.and_then(|meta| ScanArgs::from_meta(&meta)),
+            };
+            let scan_args = match scan_args {
+                Ok(scan_args) => scan_args,
+                Err(e) => env
+                    .logr
+                    .abort(e.span(), format!("Invalid `scan` declaration: {e}")),
+            };
+            scan_declarations(&scan_args, env)
+        }
+        _ => (vec![input], TokenStream::new()),
+    }
+}
+
+/// Arguments of the `scan` declaration, forwarded to every discovered declaration
+#[derive(Debug, Default, FromMeta)]
+#[darling(default)]
+struct ScanArgs {
+    /// The module of the crate root holding the implementation modules
+    impls_mod: Option<Ident>,
+    /// The name of the arguments struct of the implementation modules
+    #[darling(rename = "args")]
+    exec_args_ident: Option<Ident>,
+}
+
+/// Declares every module of the implementations folder exposing an `Args` struct and an `exec` function.
+///
+/// The macro kind is inferred from the `exec` signature:
+/// - `exec(Args, <receiver>, ExecEnv)` declares an attribute macro on `<receiver>`
+/// - `exec(Args, ExecEnv)` declares a derive macro if `Args` derives [`darling::FromDeriveInput`],
+///   registering the helper attributes the file reads, a function-like macro otherwise
+/// - a `Result` return type declares the `syn` error strategy (see [`Errors::Syn`])
+/// - without `exec`, `exec_<item>(Args, <ItemX>, ExecEnv)` functions declare an attribute macro dispatched on these [`syn`] items,
+///   e.g. `receiver = [ItemStruct, ItemEnum]` for `exec_struct` and `exec_enum`
+///
+/// Modules are discovered with [`rsmack_utils::fs::folder_modules`] and declared in name order,
+/// reporting an error for each module which can not be declared.
+/// The discovered files and the `mod.rs` file of the folder are tracked with [`track_files`],
+/// so that the folder is scanned again when a module is added or changed.
+fn scan_declarations(scan_args: &ScanArgs, env: &ExecEnv) -> (Vec<TokenStream>, TokenStream) {
+    let impls_mod = scan_args
+        .impls_mod
+        .clone()
+        .unwrap_or_else(default_impls_mod);
+    let args_ident = scan_args
+        .exec_args_ident
+        .clone()
+        .unwrap_or_else(default_args);
+    let impls_folder = package_src_folder().join(impls_mod.to_string());
+    let mut modules = folder_modules(&impls_folder);
+    modules.sort();
+    let tracked_files = track_files(
+        &modules
+            .iter()
+            .map(|module| impls_folder.join(format!("{module}.rs")))
+            .chain(Some(impls_folder.join("mod.rs")).filter(|mod_file| mod_file.is_file()))
+            .collect::<Vec<_>>(),
+    );
+    let declarations = modules
+        .iter()
+        .filter_map(|module| {
+            let skip = |reason: String| {
+                env.logr.emit_call_site_error(format!(
+                    "Failed to declare `{module}` implementation module, {reason}"
+                ));
+                None
+            };
+            let Ok(name) = syn::parse_str::<Ident>(module) else {
+                return skip("not a valid identifier".into());
+            };
+            let impl_file = get_impl_file_summary(&impls_mod, &name, &args_ident, env);
+            let Some(args_struct) = impl_file.args_struct.as_ref() else {
+                return skip(format!("missing `{args_ident}` struct"));
+            };
+            let config = |returns_result: bool| {
+                let errors = returns_result.then(|| quote!(, errors = syn));
+                quote! { impls_mod = #impls_mod, args = #args_ident #errors }
+            };
+            let Some(exec_fn) = impl_file.exec_fn("exec") else {
+                let receivers = dispatched_receivers(&impl_file);
+                if receivers.is_empty() {
+                    return skip("missing `exec` or `exec_<item>` functions".into());
+                }
+                let config = config(receivers.iter().any(|receiver| {
+                    impl_file
+                        .exec_fn(&dispatch_exec_fn(receiver).to_string())
+                        .is_some_and(ExecFnSummary::returns_result)
+                }));
+                return Some(
+                    quote! { kind = Attr, name = #name, receiver = [#(#receivers),*], #config },
+                );
+            };
+            let config = config(exec_fn.returns_result());
+            match exec_fn.params.len() {
+                3 => {
+                    let Ok(receiver) = syn::parse_str::<Path>(&exec_fn.params[1]) else {
+                        return skip(format!(
+                            "the receiver of `exec` must be a type path taken by value, found `{}`",
+                            exec_fn.params[1]
+                        ));
+                    };
+                    let receiver = match receiver.get_ident() {
+                        // Types other than syn ones are resolved from the implementation module
+                        Some(ident) if !SYN_RECEIVERS.contains(&ident.to_string().as_str()) => {
+                            parse_quote!(crate::#impls_mod::#name::#ident)
+                        }
+                        _ => receiver,
+                    };
+                    Some(quote! { kind = Attr, name = #name, receiver = #receiver, #config })
+                }
+                2 if args_struct.implements("FromDeriveInput") => {
+                    let helper_attrs = impl_file
+                        .read_helper_attrs
+                        .iter()
+                        .map(|helper_attr| format_ident!("{helper_attr}"));
+                    Some(
+                        quote! { kind = Derive, name = #name, helper_attrs = [#(#helper_attrs),*], #config },
+                    )
+                }
+                2 => Some(quote! { kind = Func, name = #name, #config }),
+                params => skip(format!(
+                    "`exec` takes {params} parameters, expected 2 or 3"
+                )),
+            }
+        })
+        .collect();
+    (declarations, tracked_files)
+}
+
+/// The [`syn`] item types dispatched to the `exec_<item>(Args, <ItemX>, ExecEnv)` functions of an implementation file
+fn dispatched_receivers(impl_file: &ImplFileSummary) -> Vec<Ident> {
+    SYN_RECEIVERS[1..]
+        .iter()
+        .map(|receiver| format_ident!("{receiver}"))
+        .filter(|receiver| {
+            impl_file
+                .exec_fn(&dispatch_exec_fn(receiver).to_string())
+                .is_some_and(|exec_fn| {
+                    exec_fn.params.len() == 3
+                        && param_ty_ident(&exec_fn.params[1]).is_some_and(|ty| *receiver == ty)
+                })
+        })
+        .collect()
+}
+
+/// Resolves the receiver of the generated macro.
 ///
-/// # Panics
-/// Panics if the file cannot be read or parsed.
-fn get_macro_impl_file_ast(args: &Args, env: &ExecEnv) -> File {
-    let package_src_folder = package_src_folder();
-    let macro_impl_file_path = package_src_folder
-        .join(env.implementations_mod_ident.clone())
-        .join(format!("{}.rs", args.name));
-    let macro_impl_src =
-        std::fs::read_to_string(macro_impl_file_path.clone()).unwrap_or_else(|_| {
-            panic!(
-                "Failed to get macro_impl_src of {} at {}",
+/// A bare ident must be one of [`SYN_RECEIVERS`] and is resolved as `syn::<ident>`,
+/// any other path is used as is and must point to a type implementing [`syn::parse::Parse`].
+/// A list of several receivers is a [`Receiver::Dispatch`] and must only hold bare [`syn`] item idents other than `Item`.
+///
+/// # Aborts
+/// Aborts compilation if the receiver is missing for an attribute macro, is an unknown bare ident,
+/// or if a dispatched receiver is not a [`syn`] item ident.
+fn get_receiver_ty(args: &Args, kind: MacroKind, env: &ExecEnv) -> Option<Receiver> {
+    let receivers = match (&args.receiver, kind) {
+        (None, MacroKind::Attr) => env.logr.abort(
+            args.name.span(),
+            format!(
+                "Missing `receiver` for `{}` attribute macro, expected one of {}",
                 args.name,
-                macro_impl_file_path.display()
-            )
-        });
+                fmt_syn_receivers()
+            ),
+        ),
+        (Some(List(receivers)), MacroKind::Func | MacroKind::Derive) => {
+            env.logr.emit_error(
+                receivers.first().map_or(args.name.span(), Spanned::span),
+                format!("`receiver` is only used with `Attr` macros, not `{kind}`"),
+            );
+            return None;
+        }
+        (None, _) => return None,
+        (Some(List(receivers)), MacroKind::Attr) => receivers,
+    };
+    match receivers.as_slice() {
+        [] => env.logr.abort(
+            args.name.span(),
+            format!(
+                "Empty `receiver` list for `{}` attribute macro, expected some of {}",
+                args.name,
+                fmt_syn_receivers()
+            ),
+        ),
+        [receiver] => Some(Receiver::Single(get_single_receiver_ty(receiver, env))),
+        receivers => Some(Receiver::Dispatch(
+            receivers
+                .iter()
+                .map(|receiver| match receiver.get_ident() {
+                    Some(ident)
+                        if ident != "Item"
+                            && SYN_RECEIVERS.contains(&ident.to_string().as_str()) =>
+                    {
+                        ident.clone()
+                    }
+                    _ => env.logr.abort(
+                        receiver.span(),
+                        format!(
+                            "Unsupported dispatched receiver `{}`, expected one of {}",
+                            receiver.to_token_stream().to_string().replace(' ', ""),
+                            fmt_receivers(&SYN_RECEIVERS[1..])
+                        ),
+                    ),
+                })
+                .collect(),
+        )),
+    }
+}
 
-    match syn::parse_file(&macro_impl_src) {
-        Ok(x) => x,
-        Err(e) => env.logr.abort_call_site(format!(
-            "Failed to parse macro_impl_src {}, this may happen for no real reason in your IDE, check that your project still build with cargo: {e:?}",
-            args.name.clone()
-        ))
+/// Resolves a single receiver type, see [`get_receiver_ty`].
+///
+/// # Aborts
+/// Aborts compilation if the receiver is an unknown bare ident.
+fn get_single_receiver_ty(receiver: &Path, env: &ExecEnv) -> Path {
+    match receiver.get_ident() {
+        Some(ident) if SYN_RECEIVERS.contains(&ident.to_string().as_str()) => {
+            parse_quote!(syn::#ident)
+        }
+        Some(ident) => env.logr.abort(
+            ident.span(),
+            format!(
+                "Unsupported receiver `{ident}`, expected one of {} or a path to a type implementing `syn::parse::Parse`",
+                fmt_syn_receivers()
+            ),
+        ),
+        None => receiver.clone(),
+    }
+}
+
+/// [`darling::FromDeriveInput`] fields forwarded from the derive input rather than parsed from helper attributes
+const DERIVE_INPUT_FORWARDED_FIELDS: &[&str] = &["ident", "vis", "generics", "data", "attrs"];
+
+/// Checks that the arguments struct of a derive macro implements [`darling::FromDeriveInput`],
+/// which parses it from the derive input.
+fn check_derive_args_struct(args_struct: &ArgsStructSummary, args: &Args, env: &ExecEnv) {
+    if !args_struct.implements("FromDeriveInput") {
+        env.logr.emit_error(
+            args.exec_args_ident.span(),
+            format!(
+                "`{}` of `{}` must implement `FromDeriveInput` for `Derive` macros, e.g. with `#[derive(FromDeriveInput)]`",
+                args.exec_args_ident, args.name
+            ),
+        );
+    }
+}
+
+/// Rejects the helper attributes which no `#[darling(attributes(..))]` of the implementation file reads.
+///
+/// Without it, [`darling::FromDeriveInput`] (or [`darling::FromField`], [`darling::FromVariant`], ...) ignores the helper attributes
+/// registered by the generated `proc_macro_derive`.
+fn check_derive_helper_attrs(
+    impl_file: &ImplFileSummary,
+    helper_attrs: &[Ident],
+    args: &Args,
+    env: &ExecEnv,
+) {
+    for helper_attr in helper_attrs
+        .iter()
+        .filter(|h| !impl_file.read_helper_attrs.contains(&h.to_string()))
+    {
+        env.logr.emit_error(
+            helper_attr.span(),
+            format!(
+                "`{}` derive helper attribute `{helper_attr}` is not read, `{}` (or a type of its fields) must declare `#[darling(attributes({helper_attr}))]`",
+                args.name, args.exec_args_ident
+            ),
+        );
+    }
+}
+
+/// Formats [`SYN_RECEIVERS`] as a comma separated list of code spans
+fn fmt_syn_receivers() -> String {
+    fmt_receivers(SYN_RECEIVERS)
+}
+
+/// Formats receivers as a comma separated list of code spans
+fn fmt_receivers<T: std::fmt::Display>(receivers: &[T]) -> String {
+    receivers
+        .iter()
+        .map(|r| format!("`{r}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Checks the implementation functions of the macro against its kind and receiver, see [`check_exec_signature`].
+///
+/// A [`Receiver::Dispatch`] is checked against `exec(Args, Item, ExecEnv)` if the implementation file declares
+/// an `exec` taking 3 parameters, against the `exec_<item>` function of each receiver otherwise.
+///
+/// Returns whether the dispatched items are given to a single `exec`.
+fn check_exec_signatures(
+    impl_file: &ImplFileSummary,
+    kind: MacroKind,
+    receiver: Option<&Receiver>,
+    args: &Args,
+    env: &ExecEnv,
+) -> bool {
+    let receiver_span = || {
+        args.receiver
+            .as_ref()
+            .and_then(|List(receivers)| receivers.first())
+            .map_or(args.kind.span(), Spanned::span)
+    };
+    match receiver {
+        Some(Receiver::Single(receiver)) => {
+            check_exec_signature(
+                impl_file,
+                "exec",
+                kind,
+                Some((receiver, receiver_span())),
+                args,
+                env,
+            );
+            false
+        }
+        Some(Receiver::Dispatch(receivers)) => {
+            let dispatch_exec = impl_file
+                .exec_fn("exec")
+                .is_some_and(|exec_fn| exec_fn.params.len() == 3);
+            if dispatch_exec {
+                let item: Path = parse_quote!(syn::Item);
+                check_exec_signature(
+                    impl_file,
+                    "exec",
+                    kind,
+                    Some((&item, receiver_span())),
+                    args,
+                    env,
+                );
+            } else {
+                for receiver in receivers {
+                    let exec_fn = dispatch_exec_fn(receiver).to_string();
+                    let receiver_ty: Path = parse_quote!(syn::#receiver);
+                    check_exec_signature(
+                        impl_file,
+                        &exec_fn,
+                        kind,
+                        Some((&receiver_ty, receiver.span())),
+                        args,
+                        env,
+                    );
+                }
+            }
+            dispatch_exec
+        }
+        None => {
+            check_exec_signature(impl_file, "exec", kind, None, args, env);
+            false
+        }
+    }
+}
+
+/// Checks that `pub fn <exec_fn>` of the macro implementation file has the signature expected by the macro kind
+/// and returns a `Result` with the `syn` error strategy only.
+///
+/// - [`MacroKind::Func`]: `exec(Args, ExecEnv)`
+/// - [`MacroKind::Attr`]: `exec(Args, <receiver>, ExecEnv)`
+/// - [`MacroKind::Derive`]: `exec(Args, ExecEnv)`, `Args` implementing [`darling::FromDeriveInput`] (see [`check_derive_args_struct`])
+///
+/// Parameter types are parsed back as [`Type`]s and only the last segment of a path type is compared,
+/// as the implementation file may import the types. References and other types never match.
+/// Errors point at the `megamac` argument the mismatching parameter derives from.
+///
+/// # Aborts
+/// Aborts compilation if the function can not be found.
+fn check_exec_signature(
+    impl_file: &ImplFileSummary,
+    exec_fn_name: &str,
+    kind: MacroKind,
+    receiver: Option<(&Path, Span)>,
+    args: &Args,
+    env: &ExecEnv,
+) {
+    let Some(exec_fn) = impl_file.exec_fn(exec_fn_name) else {
+        env.logr.abort(
+            receiver.map_or(args.name.span(), |(_, span)| span),
+            format!(
+                "Failed to find `pub fn {exec_fn_name}` in `{}::{}` module",
+                args.impls_mod, args.name
+            ),
+        );
+    };
+    if !exec_fn.public {
+        env.logr.emit_error(
+            args.name.span(),
+            format!("`{exec_fn_name}` of `{}` must be `pub`", args.name),
+        );
+    }
+    match (args.errors, exec_fn.returns_result()) {
+        (Errors::ProcMacroError2, true) => env.logr.emit_error(
+            args.name.span(),
+            format!(
+                "`{exec_fn_name}` of `{}` returns `{}`, declare it with `errors = syn`",
+                args.name, exec_fn.output
+            ),
+        ),
+        (Errors::Syn, false) => env.logr.emit_error(
+            args.name.span(),
+            format!(
+                "`{exec_fn_name}` of `{}` must return `syn::Result<TokenStream>` with `errors = syn`, found `{}`",
+                args.name, exec_fn.output
+            ),
+        ),
+        _ => {}
+    }
+
+    let kind_span = args.kind.span();
+    let mut expected_params = match (kind, receiver) {
+        (MacroKind::Attr, Some((receiver, receiver_span))) => vec![
+            (args.exec_args_ident.to_string(), kind_span),
+            (
+                receiver.segments.last().unwrap().ident.to_string(),
+                receiver_span,
+            ),
+        ],
+        (MacroKind::Func | MacroKind::Attr | MacroKind::Derive, _) => {
+            vec![(args.exec_args_ident.to_string(), kind_span)]
+        }
+    };
+    expected_params.push(("ExecEnv".to_string(), kind_span));
+
+    let found_params = &exec_fn.params;
+    let fmt_params = |params: &[String]| format!("fn {exec_fn_name}({})", params.join(", "));
+
+    if found_params.len() != expected_params.len() {
+        let expected = expected_params
+            .iter()
+            .map(|(ident, _)| ident.clone())
+            .collect::<Vec<_>>();
+        env.logr.emit_error(
+            kind_span,
+            format!(
+                "`{exec_fn_name}` of `{}` must take {} parameters for `{kind}` macros: `{}`, found `{}`",
+                args.name,
+                expected.len(),
+                fmt_params(&expected),
+                fmt_params(found_params),
+            ),
+        );
+        return;
+    }
+    for (i, ((expected, span), found)) in expected_params.iter().zip(found_params).enumerate() {
+        if param_ty_ident(found).is_some_and(|ident| ident == expected) {
+            continue;
+        }
+        let by_value = if matches!(syn::parse_str(found), Ok(Type::Reference(_))) {
+            ", taken by value"
+        } else {
+            ""
+        };
+        env.logr.emit_error(
+            *span,
+            format!(
+                "Parameter {} of `{exec_fn_name}` of `{}` must be `{expected}`{by_value} for `{kind}` macros, found `{found}`",
+                i + 1,
+                args.name,
+            ),
+        );
     }
 }
 
-/// Documentation information for a field in the macro arguments struct.
-#[derive(Debug, Builder)]
-struct FieldDoc {
-    /// The field identifier
-    pub ident: Ident,
-    /// The field documentation string, if present
-    pub doc: Option<String>,
-    /// The field type
-    pub ty: Type,
+/// The ident of the last segment of a parameter type given as a path without generic arguments,
+/// e.g. `ItemStruct` for `syn::ItemStruct`, [`None`] for `&ItemStruct` or `Box<ItemStruct>`
+fn param_ty_ident(param: &str) -> Option<Ident> {
+    match syn::parse_str(param).ok()? {
+        Type::Path(TypePath { qself: None, path }) => path
+            .segments
+            .last()
+            .filter(|segment| segment.arguments.is_none())
+            .map(|segment| segment.ident.clone()),
+        _ => None,
+    }
 }
 
-/// Extracts documentation for all fields in the macro arguments struct.
+/// Gets the summary of the macro implementation file, embedded at build time if available
+/// (see [`rsmack_utils::megamac::impl_file::embed_impl_files`]), read and cached otherwise.
+/// A malformed embedded summary is reported as an error, the file being read instead.
 ///
 /// # Arguments
-/// - `macro_impl_file_ast`: The AST of the macro implementation file
-/// - `args`: The macro configuration arguments
+/// - `impls_mod`: The module holding the implementation modules
+/// - `name`: The name of the macro, which is also its implementation module name
+/// - `exec_args_ident`: The name of the arguments struct
 /// - `env`: The execution environment
 ///
-/// # Returns
-/// A vector of [`FieldDoc`] containing documentation for each field.
+/// # Aborts
+/// Aborts compilation if the file cannot be read or parsed.
+fn get_impl_file_summary(
+    impls_mod: &Ident,
+    name: &Ident,
+    exec_args_ident: &Ident,
+    env: &ExecEnv,
+) -> Arc<ImplFileSummary> {
+    match ImplFileSummary::embedded(
+        &impls_mod.to_string(),
+        &name.to_string(),
+        &exec_args_ident.to_string(),
+    ) {
+        Some(Ok(summary)) => return Arc::new(summary),
+        Some(Err(e)) => env.logr.emit_call_site_error(format!(
+            "Malformed embedded summary of `{impls_mod}::{name}`: {e}"
+        )),
+        None => {}
+    }
+    let macro_impl_file_path = package_src_folder()
+        .join(impls_mod.to_string())
+        .join(format!("{name}.rs"));
+    match ImplFileSummary::load(&macro_impl_file_path, &exec_args_ident.to_string()) {
+        Ok(summary) => summary,
+        Err(e) => env.logr.abort_call_site(format!(
+            "Failed to get the implementation of {name}, this may happen for no real reason in your IDE, check that your project still build with cargo: {e}"
+        )),
+    }
+}
+
+/// Finds the macro arguments struct in the macro implementation file.
 ///
 /// # Aborts
 /// Aborts compilation if the arguments struct cannot be found.
-fn get_args_fields_doc(macro_impl_file_ast: &File, args: &Args, env: &ExecEnv) -> Vec<FieldDoc> {
-    let args_item = macro_impl_file_ast.items.iter().find(|i| match i {
-        Item::Struct(ItemStruct { ident, .. }) => *ident == env.exec_args_ident,
-        _ => false,
+fn get_args_struct<'a>(
+    impl_file: &'a ImplFileSummary,
+    args: &Args,
+    env: &ExecEnv,
+) -> &'a ArgsStructSummary {
+    match &impl_file.args_struct {
+        Some(args_struct) => args_struct,
+        None => env.logr.abort_call_site(format!(
+            "Failed to find `{}` struct in `{}::{}` module",
+            args.exec_args_ident, args.impls_mod, args.name
+        )),
+    }
+}
+
+/// Generates the documentation included from the [`Readme`] file of the macro.
+///
+/// The file is included with [`include_str`] from its absolute path, so that it does not depend on the invocation file location.
+fn get_readme_doc(args: &Args, env: &ExecEnv) -> TokenStream {
+    let package_folder = package_folder();
+    let readme_path = match &args.readme {
+        Readme::Disabled => None,
+        Readme::Lookup => [
+            package_folder
+                .join("docs")
+                .join(format!("{}.md", args.name)),
+            package_folder.join("README.md"),
+        ]
+        .into_iter()
+        .find(|path| path.is_file()),
+        Readme::Path(path) => {
+            let readme_path = package_folder.join(path.value());
+            if readme_path.is_file() {
+                Some(readme_path)
+            } else {
+                env.logr.emit_error(
+                    path.span(),
+                    format!(
+                        "Readme file of `{}` not found at `{}`, use `readme = false` to disable it",
+                        args.name,
+                        readme_path.display()
+                    ),
+                );
+                None
+            }
+        }
+    };
+    readme_path
+        .map(|readme_path| {
+            let readme_path = readme_path.display().to_string();
+            quote! { #[doc = include_str!(#readme_path)] }
+        })
+        .unwrap_or_default()
+}
+
+/// Generates the examples documentation of the macro.
+///
+/// The `# Examples` section gathers the `# Examples` section of the arguments struct documentation
+/// and the `examples/<name>.rs` file of the package, the `# Compile fail` section the `examples/<name>_fail.rs` file.
+/// Example files are included with [`include_str`] so that they stay in sync with the documentation.
+fn get_examples_doc(args_struct: &ArgsStructSummary, args: &Args) -> TokenStream {
+    let examples_folder = package_folder().join("examples");
+    let include_example = |file_name: String, code_block_attrs: &str| {
+        let example_path = examples_folder.join(file_name);
+        example_path.is_file().then(|| {
+            let example_path = example_path.display().to_string();
+            let code_block_start = format!("```{code_block_attrs}\n");
+            quote! { #[doc = concat!(#code_block_start, include_str!(#example_path), "\n```")] }
+        })
+    };
+
+    let mut examples = vec![];
+    if let Some(args_examples) = &args_struct.examples_doc {
+        examples.push(quote! { #[doc = #args_examples] });
+    }
+    examples.extend(include_example(format!("{}.rs", args.name), "rust"));
+    let examples = (!examples.is_empty()).then(|| {
+        quote! {
+            #[doc = "# Examples"]
+            #(#examples)*
+        }
     });
+    let fail_example =
+        include_example(format!("{}_fail.rs", args.name), "compile_fail").map(|fail_example| {
+            quote! {
+                #[doc = "# Compile fail"]
+                #fail_example
+            }
+        });
+    quote! {
+        #examples
+        #fail_example
+    }
+}
 
-    if let Some(args_item) = args_item {
-        let fields_doc = match args_item {
-            Item::Struct(ItemStruct { fields, .. }) => fields
-                .iter()
-                .flat_map(|f| {
-                    f.attrs
-                        .iter()
-                        .map(|a| match a.meta.clone() {
-                            // Extract documentation from #[doc = "..."] attributes
-                            Meta::NameValue(MetaNameValue {
-                                value:
-                                    Expr::Lit(ExprLit {
-                                        lit: Lit::Str(lit_str),
-                                        ..
-                                    }),
-                                path: Path { segments, .. },
-                                ..
-                            }) => {
-                                let PathSegment { ident, .. } = segments.first().unwrap();
-                                if *ident == "doc" {
-                                    FieldDoc::builder()
-                                        .ident(f.ident.clone().unwrap())
-                                        .doc(lit_str.value())
-                                        .ty(f.ty.clone())
-                                        .build()
-                                } else {
-                                    FieldDoc::builder()
-                                        .ident(f.ident.clone().unwrap())
-                                        .ty(f.ty.clone())
-                                        .build()
-                                }
-                            }
-                            _ => unimplemented!(),
-                        })
-                        .collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>(),
-            _ => unimplemented!(),
+#[cfg(test)]
+mod tests {
+    use proc_macro2::{Delimiter, TokenStream, TokenTree};
+    use quote::quote;
+    use rsmack_utils::{
+        exec::exec_syn_status,
+        fs::package_src_folder,
+        megamac::impl_file::ImplFileSummary,
+        testing::{expand, expand_func, test_env, Expansion},
+    };
+
+    use syn::Expr;
+
+    use super::{declarations, param_ty_ident};
+    use crate::fixtures::fallible;
+
+    /// Expand the declarations of a `megamac!` input, each bracketed, followed by the tracked files
+    fn expand_declarations(input: TokenStream) -> Expansion {
+        expand(test_env!(impls, Args, megamac, Func), |env| {
+            let (declarations, tracked_files) = declarations(input, &env);
+            Ok(quote!(#([#declarations])* #tracked_files))
+        })
+    }
+
+    /// The files included with `include_bytes!` by an expansion, relative to the package source folder
+    fn tracked_files(expansion: &Expansion) -> Vec<String> {
+        let src = format!("{}/", package_src_folder().display());
+        let output = expansion.output.as_ref().unwrap().to_string();
+        output
+            .split("include_bytes ! (\"")
+            .skip(1)
+            .map(|split| split[..split.find('"').unwrap()].replace(&src, ""))
+            .collect()
+    }
+
+    #[test]
+    fn splits_list_declarations() {
+        let expansion = expand_declarations(quote!(
+            [kind = Func, name = func],
+            [kind = Derive, name = derive]
+        ));
+        assert_eq!(
+            expansion.output.unwrap().to_string(),
+            quote!([kind = Func, name = func][kind = Derive, name = derive]).to_string()
+        );
+    }
+
+    #[test]
+    fn scan_infers_each_kind() {
+        let expansion = expand_declarations(quote!(scan(impls_mod = fixtures)));
+        let config = quote!(impls_mod = fixtures, args = Args);
+        let declarations = [
+            quote!([kind = Attr, name = attr, receiver = ItemStruct, #config]),
+            quote!([kind = Derive, name = derive, helper_attrs = [], #config]),
+            quote!([kind = Attr, name = dispatch, receiver = [ItemEnum, ItemStruct], #config]),
+            quote!([kind = Func, name = fallible, #config, errors = syn]),
+            quote!([kind = Func, name = func, #config]),
+            quote!([kind = Derive, name = helper, helper_attrs = [helper], #config]),
+            quote!([kind = Func, name = sum, #config]),
+        ];
+        let output = expansion.output.clone().unwrap().into_iter();
+        let scanned = output
+            .take_while(
+                |tt| matches!(tt, TokenTree::Group(g) if g.delimiter() == Delimiter::Bracket),
+            )
+            .map(|tt| tt.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(scanned, declarations.map(|d| d.to_string()));
+        assert_eq!(
+            expansion.errors(),
+            [
+                "#[rsmack_megamac::megamac] Failed to declare `by_ref` implementation module, the receiver of `exec` must be a type path taken by value, found `&ItemStruct`",
+                "#[rsmack_megamac::megamac] Failed to declare `opts` implementation module, missing `Args` struct",
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_tracks_the_folder_modules() {
+        let expansion = expand_declarations(quote!(scan(impls_mod = fixtures)));
+        assert_eq!(
+            tracked_files(&expansion),
+            [
+                "fixtures/attr.rs",
+                "fixtures/by_ref.rs",
+                "fixtures/derive.rs",
+                "fixtures/dispatch.rs",
+                "fixtures/fallible.rs",
+                "fixtures/func.rs",
+                "fixtures/helper.rs",
+                "fixtures/opts.rs",
+                "fixtures/sum.rs",
+                "fixtures/mod.rs"
+            ]
+        );
+        let expansion = expand_declarations(quote!(kind = Func, name = func));
+        assert_eq!(tracked_files(&expansion), Vec::<String>::new());
+    }
+
+    #[test]
+    fn compares_the_last_segment_of_path_param_types() {
+        let ident = |param: &str| param_ty_ident(param).map(|ident| ident.to_string());
+        assert_eq!(ident("ItemStruct").as_deref(), Some("ItemStruct"));
+        assert_eq!(ident("syn :: ItemStruct").as_deref(), Some("ItemStruct"));
+        assert_eq!(ident("&ItemStruct"), None);
+        assert_eq!(ident("Box<ItemStruct>"), None);
+        assert_eq!(ident("<T as Tr>::ItemStruct"), None);
+    }
+
+    #[test]
+    fn rejects_receiver_taken_by_reference() {
+        let expansion = expand_func!(
+            impls,
+            Args,
+            megamac,
+            quote!(
+                kind = Attr,
+                name = by_ref,
+                receiver = ItemStruct,
+                impls_mod = fixtures
+            )
+        );
+        assert_eq!(
+            expansion.errors(),
+            ["#[rsmack_megamac::megamac] Parameter 2 of `exec` of `by_ref` must be `ItemStruct`, taken by value for `Attr` macros, found `&ItemStruct`"]
+        );
+    }
+
+    #[test]
+    fn reads_the_file_of_args_other_than_the_embedded_ones() {
+        // Embedded by a build script for the default `Args` struct, which `opts` does not have
+        std::env::set_var(
+            ImplFileSummary::env_var("fixtures", "opts", "Args"),
+            r#"{"exec_fns":[],"args_struct":null,"read_helper_attrs":[]}"#,
+        );
+        let expansion = expand_func!(
+            impls,
+            Args,
+            megamac,
+            quote!(kind = Func, name = opts, args = Opts, impls_mod = fixtures)
+        );
+        assert_eq!(expansion.messages.len(), 0);
+        assert!(expansion.output.is_some());
+    }
+
+    #[test]
+    fn dispatches_struct_and_enum_items() {
+        let expansion = expand_func!(
+            impls,
+            Args,
+            megamac,
+            quote!(
+                kind = Attr,
+                name = dispatch,
+                receiver = [ItemStruct, ItemEnum],
+                impls_mod = fixtures
+            )
+        );
+        assert_eq!(expansion.messages.len(), 0);
+        let dispatch = quote! {
+            match item {
+                syn::Item::Struct(item) => crate::fixtures::dispatch::exec_struct(args, item, env),
+                syn::Item::Enum(item) => crate::fixtures::dispatch::exec_enum(args, item, env),
+                item => env.logr.abort(
+                    syn::spanned::Spanned::span(&item),
+                    "Unsupported item for `dispatch`, expected one of `ItemStruct`, `ItemEnum`"
+                ),
+            }
         };
-        fields_doc
-    } else {
-        env.logr.abort_call_site(format!(
-            "Failed to find `{}` struct in `{}` module",
-            env.exec_args_ident,
-            args.name.clone()
+        assert!(expansion
+            .output
+            .unwrap()
+            .to_string()
+            .contains(&dispatch.to_string()));
+    }
+
+    #[test]
+    fn rejects_unsupported_dispatched_receiver() {
+        let expansion = expand_func!(
+            impls,
+            Args,
+            megamac,
+            quote!(
+                kind = Attr,
+                name = dispatch,
+                receiver = [ItemStruct, ItemFoo],
+                impls_mod = fixtures
+            )
+        );
+        assert!(expansion.output.is_none());
+        assert!(expansion.errors()[0].starts_with(
+            "#[rsmack_megamac::megamac] Unsupported dispatched receiver `ItemFoo`, expected one of `ItemConst`"
         ));
     }
+
+    #[test]
+    fn rejects_attribute_macro_without_receiver() {
+        let expansion = expand_func!(
+            impls,
+            Args,
+            megamac,
+            quote!(kind = Attr, name = attr, impls_mod = fixtures)
+        );
+        assert!(expansion.errors()[0].starts_with(
+            "#[rsmack_megamac::megamac] Missing `receiver` for `attr` attribute macro"
+        ));
+    }
+
+    #[test]
+    fn requires_syn_errors_for_result_returning_exec() {
+        let declare = |errors| {
+            expand_func!(
+                impls,
+                Args,
+                megamac,
+                quote!(kind = Func, name = fallible, impls_mod = fixtures #errors)
+            )
+        };
+        assert_eq!(
+            declare(quote!()).errors(),
+            ["#[rsmack_megamac::megamac] `exec` of `fallible` returns `syn::Result<TokenStream>`, declare it with `errors = syn`"]
+        );
+        assert_eq!(declare(quote!(, errors = syn)).messages.len(), 0);
+    }
+
+    #[test]
+    fn detects_parse_input() {
+        let expansion = expand_func!(
+            impls,
+            Args,
+            megamac,
+            quote!(kind = Func, name = sum, impls_mod = fixtures)
+        );
+        assert_eq!(expansion.messages.len(), 0);
+        let output = expansion.output.unwrap().to_string();
+        let call = quote!(rsmack_utils::exec::call_func_proc_macro!(
+            fixtures,
+            Args,
+            sum,
+            args,
+            input = Parse
+        ));
+        assert!(output.contains(&call.to_string()));
+        assert!(!output.contains("# Parameters"));
+    }
+
+    #[test]
+    fn requires_from_derive_input_args_for_derive() {
+        let expansion = expand_func!(
+            impls,
+            Args,
+            megamac,
+            quote!(
+                kind = Derive,
+                name = func,
+                helper_attrs = [],
+                impls_mod = fixtures
+            )
+        );
+        assert_eq!(
+            expansion.errors(),
+            ["#[rsmack_megamac::megamac] `Args` of `func` must implement `FromDeriveInput` for `Derive` macros, e.g. with `#[derive(FromDeriveInput)]`"]
+        );
+    }
+
+    #[test]
+    fn declares_derive_name_and_helper_attrs() {
+        let expansion = expand_func!(
+            impls,
+            Args,
+            megamac,
+            quote!(
+                kind = Derive,
+                name = helper,
+                derive_name = Helped,
+                helper_attrs = [helper],
+                impls_mod = fixtures
+            )
+        );
+        assert_eq!(expansion.messages.len(), 0);
+        assert!(expansion
+            .output
+            .unwrap()
+            .to_string()
+            .contains(&quote!(#[proc_macro_derive(Helped, attributes(helper))]).to_string()));
+    }
+
+    #[test]
+    fn rejects_helper_attrs_not_read_by_args() {
+        let expansion = expand_func!(
+            impls,
+            Args,
+            megamac,
+            quote!(
+                kind = Derive,
+                name = helper,
+                helper_attrs = [helper, other],
+                impls_mod = fixtures
+            )
+        );
+        assert_eq!(
+            expansion.errors(),
+            ["#[rsmack_megamac::megamac] `helper` derive helper attribute `other` is not read, `Args` (or a type of its fields) must declare `#[darling(attributes(other))]`"]
+        );
+    }
+
+    #[test]
+    fn rejects_derive_options_of_other_kinds() {
+        let expansion = expand_func!(
+            impls,
+            Args,
+            megamac,
+            quote!(
+                kind = Func,
+                name = func,
+                derive_name = Func,
+                helper_attrs = [func],
+                impls_mod = fixtures
+            )
+        );
+        assert_eq!(
+            expansion.errors(),
+            [
+                "#[rsmack_megamac::megamac] `derive_name` is only used with `Derive` macros, not `Func`",
+                "#[rsmack_megamac::megamac] `helper_attrs` is only used with `Derive` macros, not `Func`",
+            ]
+        );
+    }
+
+    #[test]
+    fn emits_every_syn_error_in_expression_position() {
+        let env = test_env!(fixtures, Args, fallible, Func);
+        let (output, aborted) = exec_syn_status(env, |env| fallible::exec(fallible::Args {}, env));
+        assert!(!aborted);
+        let expr = syn::parse2::<Expr>(output).expect("Expected a single expression");
+        assert_eq!(
+            quote!(#expr).to_string(),
+            quote!(::core::concat! {
+                ::core::compile_error! { "#[rsmack_megamac::fallible] Fails first" },
+                ::core::compile_error! { "Always fails" }
+            })
+            .to_string()
+        );
+    }
 }
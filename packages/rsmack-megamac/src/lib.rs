@@ -1,11 +1,25 @@
 use proc_macro::TokenStream;
 use proc_macro_error2::*;
 use rsmack_utils::*;
+#[cfg(test)]
+mod fixtures;
 mod impls;
 
-/// Declare a mega macro of any kind with automated parameters documentation
+/// Declare one or several mega macros of any kind with automated parameters documentation
+///
+/// Several macros can be declared at once with a list of bracketed declarations,
+/// or discovered from the implementations folder with `megamac!(scan)`
 #[proc_macro_error]
 #[proc_macro]
 pub fn megamac(args: TokenStream) -> TokenStream {
-    exec::call_func_impls_with_args!(megamac, args)
+    let env = build_env!(impls, Args, megamac);
+    let (declarations, tracked_files) = impls::megamac::declarations(args.into(), &env);
+    declarations
+        .into_iter()
+        .map(|declaration| -> TokenStream {
+            let args = TokenStream::from(declaration);
+            exec::call_func_impls_with_args!(megamac, args)
+        })
+        .chain(Some(tracked_files.into()))
+        .collect()
 }
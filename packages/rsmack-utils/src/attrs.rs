@@ -0,0 +1,191 @@
+//! Helper attributes of **proc-macro** items, e.g. `#[edoc(..)]` on the fields of a struct annotated with `#[edoc]`:
+//! parsed with [darling] from the members carrying them, then stripped so that rustc does not reject them.
+use darling::{FromField, FromVariant};
+use syn::{punctuated::Punctuated, Attribute, Fields, ItemEnum, ItemStruct, Token, Variant};
+
+/// Position of an item member carrying helper attributes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Member {
+    /// A struct field, by index
+    Field(usize),
+    /// An enum variant, by index
+    Variant(usize),
+    /// A field of an enum variant, by variant and field indexes
+    VariantField(usize, usize),
+}
+
+/// Helper attributes arguments parsed from an item member
+#[derive(Debug)]
+pub struct MemberAttrs<A> {
+    /// The member carrying the helper attributes
+    pub member: Member,
+    /// The index of the first helper attribute in the member attributes,
+    /// where a replacing attribute is to be inserted in the stripped attributes
+    pub attr_index: usize,
+    /// The arguments parsed from the member
+    pub args: A,
+}
+
+/// A struct stripped of its helper attributes, see [`strip_struct_attrs`]
+#[derive(Debug)]
+pub struct StrippedStruct<F> {
+    /// The struct without the helper attributes
+    pub item: ItemStruct,
+    /// The [`Member::Field`] arguments
+    pub fields: Vec<MemberAttrs<F>>,
+}
+
+/// An enum stripped of its helper attributes, see [`strip_enum_attrs`]
+#[derive(Debug)]
+pub struct StrippedEnum<V, F> {
+    /// The enum without the helper attributes
+    pub item: ItemEnum,
+    /// The [`Member::Variant`] arguments
+    pub variants: Vec<MemberAttrs<V>>,
+    /// The [`Member::VariantField`] arguments
+    pub fields: Vec<MemberAttrs<F>>,
+}
+
+/// Whether the attribute is one of the named helper attributes
+fn is_helper_attr(attr: &Attribute, names: &[&str]) -> bool {
+    names.iter().any(|name| attr.path().is_ident(name))
+}
+
+/// Parse with [`FromField`] the fields carrying one of the named helper attributes, and strip these attributes.
+///
+/// Fields without helper attributes are not parsed. Errors of every field are accumulated.
+/// # Errors
+/// If a field can not be parsed
+pub fn strip_fields_attrs<A: FromField>(
+    fields: &mut Fields,
+    names: &[&str],
+    member: impl Fn(usize) -> Member,
+) -> darling::Result<Vec<MemberAttrs<A>>> {
+    let mut errors = darling::Error::accumulator();
+    let mut parsed = vec![];
+    for (index, field) in fields.iter_mut().enumerate() {
+        let Some(attr_index) = field.attrs.iter().position(|a| is_helper_attr(a, names)) else {
+            continue;
+        };
+        if let Some(args) = errors.handle(A::from_field(field)) {
+            parsed.push(MemberAttrs {
+                member: member(index),
+                attr_index,
+                args,
+            });
+        }
+        field.attrs.retain(|a| !is_helper_attr(a, names));
+    }
+    errors.finish_with(parsed)
+}
+
+/// Parse with [`FromVariant`] the variants carrying one of the named helper attributes, and strip these attributes.
+///
+/// Variants without helper attributes are not parsed, their fields are left untouched. Errors of every variant are accumulated.
+/// # Errors
+/// If a variant can not be parsed
+/// ```
+/// use darling::FromVariant;
+/// use rsmack_utils::attrs::strip_variants_attrs;
+/// #[derive(Debug, FromVariant)]
+/// #[darling(attributes(rename))]
+/// struct Rename { to: String }
+/// let mut item: syn::ItemEnum = syn::parse_quote!(enum E { A, #[rename(to = "c")] B, #[rename(to)] C });
+/// let errors = strip_variants_attrs::<Rename>(&mut item.variants, &["rename"]).unwrap_err();
+/// assert_eq!(errors.len(), 1);
+/// // Stripped even if not parsed
+/// assert!(item.variants.iter().all(|variant| variant.attrs.is_empty()));
+/// ```
+pub fn strip_variants_attrs<A: FromVariant>(
+    variants: &mut Punctuated<Variant, Token![,]>,
+    names: &[&str],
+) -> darling::Result<Vec<MemberAttrs<A>>> {
+    let mut errors = darling::Error::accumulator();
+    let mut parsed = vec![];
+    for (index, variant) in variants.iter_mut().enumerate() {
+        let Some(attr_index) = variant.attrs.iter().position(|a| is_helper_attr(a, names)) else {
+            continue;
+        };
+        if let Some(args) = errors.handle(A::from_variant(variant)) {
+            parsed.push(MemberAttrs {
+                member: Member::Variant(index),
+                attr_index,
+                args,
+            });
+        }
+        variant.attrs.retain(|a| !is_helper_attr(a, names));
+    }
+    errors.finish_with(parsed)
+}
+
+/// Parse and strip the named helper attributes of the fields of a struct, see [`strip_fields_attrs`].
+///
+/// # Errors
+/// If a field can not be parsed
+/// ```
+/// use darling::FromField;
+/// use rsmack_utils::attrs::{strip_struct_attrs, Member};
+/// #[derive(FromField)]
+/// #[darling(attributes(rename))]
+/// struct Rename { to: String }
+/// let item = syn::parse_quote!(struct A { #[rename(to = "b")] #[allow(unused)] a: u8, c: u8 });
+/// let stripped = strip_struct_attrs::<Rename>(item, &["rename"]).unwrap();
+/// assert_eq!(stripped.fields.len(), 1);
+/// assert_eq!(stripped.fields[0].member, Member::Field(0));
+/// assert_eq!(stripped.fields[0].args.to, "b");
+/// assert_eq!(stripped.item.fields.iter().next().unwrap().attrs.len(), 1);
+/// ```
+pub fn strip_struct_attrs<A: FromField>(
+    mut item: ItemStruct,
+    names: &[&str],
+) -> darling::Result<StrippedStruct<A>> {
+    let fields = strip_fields_attrs(&mut item.fields, names, Member::Field)?;
+    Ok(StrippedStruct { item, fields })
+}
+
+/// Parse and strip the named helper attributes of the variants of an enum and of their fields,
+/// see [`strip_variants_attrs`] and [`strip_fields_attrs`].
+///
+/// Errors of the variants and of their fields are accumulated.
+/// # Errors
+/// If a variant or a field can not be parsed
+/// ```
+/// use darling::{FromField, FromVariant};
+/// use rsmack_utils::attrs::{strip_enum_attrs, Member};
+/// #[derive(FromVariant, FromField)]
+/// #[darling(attributes(rename))]
+/// struct Rename { to: String }
+/// let item = syn::parse_quote!(enum E { #[doc = "A"] #[rename(to = "b")] A { #[rename(to = "c")] a: u8 }, B });
+/// let stripped = strip_enum_attrs::<Rename, Rename>(item, &["rename"]).unwrap();
+/// assert_eq!(stripped.variants[0].member, Member::Variant(0));
+/// assert_eq!(stripped.variants[0].attr_index, 1);
+/// assert_eq!(stripped.variants[0].args.to, "b");
+/// assert_eq!(stripped.fields[0].member, Member::VariantField(0, 0));
+/// assert_eq!(stripped.fields[0].args.to, "c");
+/// assert_eq!(stripped.item.variants[0].attrs.len(), 1);
+/// ```
+pub fn strip_enum_attrs<V: FromVariant, F: FromField>(
+    mut item: ItemEnum,
+    names: &[&str],
+) -> darling::Result<StrippedEnum<V, F>> {
+    let mut errors = darling::Error::accumulator();
+    // Variants first, as a variant reading its fields sees their helper attributes
+    let variants = errors
+        .handle(strip_variants_attrs(&mut item.variants, names))
+        .unwrap_or_default();
+    let mut fields = vec![];
+    for (variant_index, variant) in item.variants.iter_mut().enumerate() {
+        fields.extend(
+            errors
+                .handle(strip_fields_attrs(&mut variant.fields, names, |index| {
+                    Member::VariantField(variant_index, index)
+                }))
+                .unwrap_or_default(),
+        );
+    }
+    errors.finish_with(StrippedEnum {
+        item,
+        variants,
+        fields,
+    })
+}
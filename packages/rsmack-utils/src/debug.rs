@@ -0,0 +1,219 @@
+//! Debug channel of **proc-macro** implementations: with `RSMACK_DEBUG=edoc,wrap`, each `exec` call
+//! of the listed macros is traced into `target/rsmack-debug/<crate>/<macro>/<n>.rs` with its input tokens, parsed arguments
+//! and pretty-printed output
+use std::{
+    fmt::{Debug, Write as _},
+    fs::OpenOptions,
+    io::Write as _,
+    path::PathBuf,
+};
+
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+
+use crate::{fs::target_folder, megamac::ExecEnv};
+
+/// Env var listing the traced macros, comma separated, `*` tracing every macro
+pub const RSMACK_DEBUG: &str = "RSMACK_DEBUG";
+
+/// Whether the macro named after its implementation module is listed in [`RSMACK_DEBUG`]
+pub fn debug_enabled(macro_name: &str) -> bool {
+    std::env::var(RSMACK_DEBUG).is_ok_and(|names| {
+        names
+            .split(',')
+            .map(str::trim)
+            .any(|name| name == "*" || name == macro_name)
+    })
+}
+
+/// Tokens pretty-printed with `prettyplease`, as is if they are not a valid file
+pub fn pretty(tokens: &TokenStream) -> String {
+    if let Ok(file) = syn::parse2::<syn::File>(tokens.clone()) {
+        return prettyplease::unparse(&file);
+    }
+    format!("{tokens}\n")
+}
+
+/// Arguments of a traced call, formatted with [`DebugArgs`] if they implement [`Debug`], with [`NoDebugArgs`] otherwise,
+/// as the method of the trait implemented without reference is selected first
+#[doc(hidden)]
+pub struct TracedArgs<'a, A>(pub &'a A);
+
+#[doc(hidden)]
+pub trait DebugArgs {
+    fn fmt_args(&self) -> String;
+}
+
+impl<A: Debug> DebugArgs for TracedArgs<'_, A> {
+    fn fmt_args(&self) -> String {
+        format!("{:#?}", self.0)
+    }
+}
+
+#[doc(hidden)]
+pub trait NoDebugArgs {
+    fn fmt_args(&self) -> String;
+}
+
+impl<A> NoDebugArgs for &TracedArgs<'_, A> {
+    fn fmt_args(&self) -> String {
+        "`Debug` not implemented".into()
+    }
+}
+
+/// Format the given arguments with [`Debug`] if they implement it
+#[doc(hidden)]
+#[macro_export]
+macro_rules! fmt_traced_args {
+    ($args:expr) => {{
+        #[allow(unused_imports)]
+        use rsmack_utils::debug::{DebugArgs as _, NoDebugArgs as _};
+        (&rsmack_utils::debug::TracedArgs(&$args)).fmt_args()
+    }};
+}
+
+/// Trace of an `exec` call, written when finished or when the call aborts, see [`Self::start`]
+#[derive(Debug)]
+pub struct DebugTrace {
+    macro_name: String,
+    crate_name: String,
+    header: String,
+    output: Option<TokenStream>,
+    aborted: bool,
+}
+
+impl DebugTrace {
+    /// Start tracing an `exec` call if its macro is enabled (see [`debug_enabled`]),
+    /// given its labeled input tokens (e.g. `attr` and `item`) and its formatted parsed arguments
+    /// ```
+    /// use quote::quote;
+    /// use rsmack_utils::{debug::{fmt_traced_args, DebugTrace, RSMACK_DEBUG}, megamac::ExecEnv};
+    /// #[derive(Debug)]
+    /// struct Args {
+    ///     flag: bool,
+    /// }
+    /// struct Syntax;
+    /// let target = std::env::temp_dir().join("rsmack-debug-trace-doctest");
+    /// let _ = std::fs::remove_dir_all(&target);
+    /// std::env::set_var("CARGO_TARGET_DIR", &target);
+    /// std::env::set_var("CARGO_CRATE_NAME", "doctest");
+    /// std::env::set_var(RSMACK_DEBUG, "my_macro");
+    /// let env = ExecEnv::builder("doctest", "impls", "Args", "my_macro").build();
+    /// let input = quote!(flag);
+    /// let traces = [
+    ///     DebugTrace::start(&env, &[("input", &input)], || fmt_traced_args!(Args { flag: true })),
+    ///     DebugTrace::start(&env, &[("input", &input)], || fmt_traced_args!(Syntax)),
+    /// ];
+    /// for trace in traces {
+    ///     DebugTrace::finish(trace, &quote!(struct Output;), false);
+    /// }
+    /// let folder = target.join("rsmack-debug").join("doctest").join("my_macro");
+    /// let trace = |n: usize| std::fs::read_to_string(folder.join(format!("{n}.rs"))).unwrap();
+    /// assert!(trace(0).contains("// --- Args\n// Args {\n//     flag: true,\n// }\n// --- output\nstruct Output;"));
+    /// assert!(trace(1).contains("// --- Args\n// `Debug` not implemented\n"));
+    /// ```
+    pub fn start(
+        env: &ExecEnv,
+        inputs: &[(&str, &dyn ToTokens)],
+        fmt_args: impl FnOnce() -> String,
+    ) -> Option<Self> {
+        let macro_name = env.exec_fn_mod_ident.clone();
+        if !debug_enabled(&macro_name) {
+            return None;
+        }
+        let crate_name = std::env::var("CARGO_CRATE_NAME").unwrap_or_else(|_| "unknown".into());
+        let mut header = format!("// `{macro_name}` {} macro in `{crate_name}`\n", env.kind);
+        for (label, tokens) in inputs {
+            writeln!(header, "// --- {label}").unwrap();
+            header.push_str(&commented(&pretty(&tokens.to_token_stream())));
+        }
+        writeln!(header, "// --- {}", env.exec_args_ident).unwrap();
+        header.push_str(&commented(&fmt_args()));
+        Some(Self {
+            macro_name,
+            crate_name,
+            header,
+            output: None,
+            aborted: false,
+        })
+    }
+    /// Finish tracing with the `exec` output, writing the trace,
+    /// `aborted` telling whether the output holds the errors of a caught abort
+    pub fn finish(trace: Option<Self>, output: &TokenStream, aborted: bool) {
+        if let Some(mut trace) = trace {
+            trace.output = Some(output.clone());
+            trace.aborted = aborted;
+        }
+    }
+    /// Write the trace to the next free `<n>.rs` file of its folder, returning its path
+    fn write(&self) -> std::io::Result<PathBuf> {
+        let folder = target_folder()
+            .join("rsmack-debug")
+            .join(&self.crate_name)
+            .join(&self.macro_name);
+        std::fs::create_dir_all(&folder)?;
+        let mut contents = self.header.clone();
+        match &self.output {
+            Some(output) => {
+                contents.push_str(if self.aborted {
+                    "// --- aborted, output\n"
+                } else {
+                    "// --- output\n"
+                });
+                contents.push_str(&pretty(output));
+            }
+            None => contents.push_str("// --- aborted\n"),
+        }
+        let mut n = std::fs::read_dir(&folder)?.count();
+        loop {
+            let path = folder.join(format!("{n}.rs"));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(contents.as_bytes())?;
+                    return Ok(path);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => n += 1,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for DebugTrace {
+    fn drop(&mut self) {
+        use build_print::*;
+        let status = if self.output.is_none() || self.aborted {
+            "aborted"
+        } else {
+            "expanded"
+        };
+        match self.write() {
+            Ok(path) => custom_println!(
+                "rsmack-debug",
+                cyan,
+                "`{}` {status} in `{}`, traced to {}",
+                self.macro_name,
+                self.crate_name,
+                path.display()
+            ),
+            Err(e) => custom_println!(
+                "rsmack-debug",
+                yellow,
+                "failed to trace `{}` in `{}`: {e}",
+                self.macro_name,
+                self.crate_name
+            ),
+        }
+    }
+}
+
+/// Prefix every line with `// `
+fn commented(text: &str) -> String {
+    text.lines().fold(String::new(), |mut commented, line| {
+        writeln!(commented, "// {line}").unwrap();
+        commented
+    })
+}
+
+#[doc(hidden)]
+pub use fmt_traced_args;
@@ -1,5 +1,206 @@
 #![allow(clippy::crate_in_macro_def)]
 //! Macro utils to call a given **proc-macro** implementation with attrs & item parsing boilerplate handled with [darling]
+use std::{
+    cell::RefCell,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use darling::{ast::NestedMeta, FromMeta};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse::Parse, parse_quote, Expr, Meta};
+
+use crate::{
+    logr::{LogrAbort, LogrLevel, LogrMessage},
+    megamac::ExecEnv,
+};
+
+/// Parse attribute proc-macro arguments with [`FromMeta::from_list`].
+///
+/// - `bare`: the arguments of a bare invocation, e.g. `#[name]`, typically [`Default::default`]
+/// - `primary`: the field set by a single unnamed value, e.g. `#[name(value)]` for `#[name(primary = value)]`.
+///   A single path is first parsed as is, so that `#[name(flag)]` still sets a `flag` field.
+///   The `#[name = value]` form can not be used, as rustc rejects key-value proc-macro attributes.
+/// # Errors
+/// If the attribute is not a valid meta list or does not match the arguments
+/// ```
+/// use darling::FromMeta;
+/// use quote::quote;
+/// use rsmack_utils::exec::parse_attr_args;
+/// #[derive(Debug, Default, PartialEq, FromMeta)]
+/// struct Args { with: Option<syn::Path>, #[darling(default)] flag: bool }
+/// let with = |args: &Args| args.with.as_ref().map(|p| quote!(#p).to_string());
+/// assert_eq!(parse_attr_args::<Args>(quote!(), Some(Args::default), None).unwrap(), Args::default());
+/// assert_eq!(with(&parse_attr_args::<Args>(quote!(Vec), None, Some("with")).unwrap()).as_deref(), Some("Vec"));
+/// assert!(parse_attr_args::<Args>(quote!(flag), None, Some("with")).unwrap().flag);
+/// assert!(parse_attr_args::<Args>(quote!(Vec), None, None).is_err());
+/// ```
+pub fn parse_attr_args<A: FromMeta>(
+    attr: TokenStream,
+    bare: Option<fn() -> A>,
+    primary: Option<&str>,
+) -> darling::Result<A> {
+    if let Some(bare) = bare.filter(|_| attr.is_empty()) {
+        return Ok(bare());
+    }
+    let meta_list = NestedMeta::parse_meta_list(attr)?;
+    let (Some(primary), [item]) = (primary, meta_list.as_slice()) else {
+        return A::from_list(&meta_list);
+    };
+    let value: Expr = match item {
+        NestedMeta::Lit(lit) => parse_quote!(#lit),
+        NestedMeta::Meta(Meta::Path(path)) => match A::from_list(&meta_list) {
+            Ok(args) => return Ok(args),
+            Err(_) => parse_quote!(#path),
+        },
+        NestedMeta::Meta(_) => return A::from_list(&meta_list),
+    };
+    let primary = syn::Ident::new(primary, proc_macro2::Span::call_site());
+    A::from_list(&[NestedMeta::Meta(parse_quote!(#primary = #value))])
+}
+
+/// Parse attribute proc-macro arguments with [`parse_attr_args`] and the item it is applied to,
+/// the errors of both being accumulated so that they are reported together.
+/// # Errors
+/// If the arguments or the item can not be parsed, with every [`syn`] error of the item
+/// ```
+/// use darling::FromMeta;
+/// use quote::quote;
+/// use rsmack_utils::exec::parse_attr_input;
+/// #[derive(Debug, FromMeta)]
+/// struct Args { with: syn::Path }
+/// let parsed = parse_attr_input::<Args, syn::ItemStruct>(quote!(with = Vec), quote!(struct A;), None, None);
+/// assert!(parsed.is_ok());
+/// let errors = parse_attr_input::<Args, syn::ItemStruct>(quote!(unknown = 1), quote!(enum A {}), None, None).unwrap_err();
+/// // The unknown field, the missing `with` field and the item errors
+/// assert_eq!(errors.len(), 3);
+/// ```
+pub fn parse_attr_input<A: FromMeta, I: Parse>(
+    attr: TokenStream,
+    item: TokenStream,
+    bare: Option<fn() -> A>,
+    primary: Option<&str>,
+) -> darling::Result<(A, I)> {
+    let args = parse_attr_args(attr, bare, primary);
+    let item = syn::parse2::<I>(item).map_err(syn_errors);
+    match (args, item) {
+        (Ok(args), Ok(item)) => Ok((args, item)),
+        (args, item) => Err(darling::Error::multiple(
+            args.err().into_iter().chain(item.err()).collect(),
+        )),
+    }
+}
+
+/// Convert a [`syn::Error`] into a [`darling::Error`] keeping each of its combined errors
+fn syn_errors(e: syn::Error) -> darling::Error {
+    darling::Error::multiple(e.into_iter().map(darling::Error::from).collect())
+}
+
+/// Parse function-like proc-macro arguments given as a meta list with [`FromMeta::from_list`]
+/// # Errors
+/// If the input is not a valid meta list or does not match the arguments
+pub fn parse_func_args<A: FromMeta>(args: TokenStream) -> darling::Result<A> {
+    A::from_list(&NestedMeta::parse_meta_list(args)?)
+}
+
+/// Parse function-like proc-macro arguments of a custom syntax with their [`Parse`] implementation
+/// # Errors
+/// If the input can not be parsed, with every combined [`syn`] error
+/// ```
+/// use quote::quote;
+/// use rsmack_utils::exec::parse_func_syntax;
+/// let exprs = parse_func_syntax::<syn::ExprArray>(quote!([1 + 1, 2])).unwrap();
+/// assert_eq!(exprs.elems.len(), 2);
+/// assert!(parse_func_syntax::<syn::ExprArray>(quote!(a = 1)).is_err());
+/// ```
+pub fn parse_func_syntax<A: Parse>(args: TokenStream) -> darling::Result<A> {
+    syn::parse2(args).map_err(syn_errors)
+}
+
+/// Call `exec` with the [`Logr`](crate::logr::Logr) of `env` capturing its messages, an abort being returned as [`None`]
+/// # Panics
+/// Resumes panics other than [`Logr`](crate::logr::Logr) aborts
+pub fn exec_captured<R, F>(mut env: ExecEnv, exec: F) -> (Option<R>, Vec<LogrMessage>)
+where
+    F: FnOnce(ExecEnv) -> R,
+{
+    let captured = Rc::new(RefCell::new(vec![]));
+    env.logr.captured = Some(captured.clone());
+    let output = match catch_unwind(AssertUnwindSafe(|| exec(env))) {
+        Ok(output) => Some(output),
+        Err(payload) if payload.is::<LogrAbort>() => None,
+        Err(payload) => resume_unwind(payload),
+    };
+    let messages = captured.take();
+    (output, messages)
+}
+
+/// Call an implementation of the `syn` error strategy, returning a [`syn::Result`] instead of emitting through [`proc_macro_error2`].
+///
+/// The [`Logr`](crate::logr::Logr) errors and then the returned error are combined and converted with [`syn::Error::to_compile_error`]
+/// in a single `concat!` invocation, so that a function-like macro reports them all in expression position too.
+/// [`Logr`](crate::logr::Logr) aborts are caught. Warnings are dropped as stable rustc has no warning API for proc-macros.
+/// ```
+/// use proc_macro2::Span;
+/// use rsmack_utils::{exec::exec_syn, megamac::ExecEnv};
+/// use rsmack_utils::megamac::MacroKind;
+/// let env = ExecEnv::builder("my_crate", "impls", "Args", "my_macro")
+///     .kind(MacroKind::Func)
+///     .build();
+/// let output = exec_syn(env, |env| {
+///     env.logr.emit_error(Span::call_site(), "first");
+///     Err(env.logr.syn_error(Span::call_site(), "second"))
+/// });
+/// let output = output.to_string();
+/// assert_eq!(output.matches("compile_error").count(), 2);
+/// assert!(output.starts_with(":: core :: concat !") && output.find("first") < output.find("second"));
+/// ```
+/// # Panics
+/// Resumes panics other than [`Logr`](crate::logr::Logr) aborts
+pub fn exec_syn<F>(env: ExecEnv, exec: F) -> TokenStream
+where
+    F: FnOnce(ExecEnv) -> syn::Result<TokenStream>,
+{
+    exec_syn_status(env, exec).0
+}
+
+/// Call an implementation with [`exec_syn`], also telling whether it aborted
+/// # Panics
+/// Resumes panics other than [`Logr`](crate::logr::Logr) aborts
+pub fn exec_syn_status<F>(env: ExecEnv, exec: F) -> (TokenStream, bool)
+where
+    F: FnOnce(ExecEnv) -> syn::Result<TokenStream>,
+{
+    let (output, messages) = exec_captured(env, exec);
+    let aborted = output.is_none();
+    let mut errors = messages
+        .iter()
+        .filter(|m| m.level == LogrLevel::Error)
+        .map(|m| syn::Error::new(m.span, m.text()))
+        .collect::<Vec<_>>();
+    let mut output = match output {
+        Some(Ok(output)) => output,
+        Some(Err(e)) => {
+            errors.push(e);
+            TokenStream::new()
+        }
+        None => TokenStream::new(),
+    };
+    let error = errors.into_iter().reduce(|mut error, e| {
+        error.combine(e);
+        error
+    });
+    output.extend(error.as_ref().map(to_compile_errors));
+    (output, aborted)
+}
+
+/// Convert the messages of a combined error to `compile_error!` invocations in a single `concat!` invocation,
+/// which expands them all in both item and expression positions
+fn to_compile_errors(error: &syn::Error) -> TokenStream {
+    let compile_errors = error.into_iter().map(|e| e.to_compile_error());
+    quote! { ::core::concat!{ #(#compile_errors),* } }
+}
 
 /// Use [`call_attr_proc_macro`] on `impls` mod with `Args` args ident
 #[macro_export]
@@ -20,13 +221,41 @@ macro_rules! call_attr_impls_with_args {
         )
     };
 }
-/// Build an [`ExecEnv`](crate::megamac::ExecEnv)
+/// Build an [`ExecEnv`](crate::megamac::ExecEnv) of the given [`MacroKind`](crate::megamac::MacroKind) variant, `Func` if omitted,
+/// optionally given the attribute arguments token stream of an attribute macro,
+/// the call site being the span of the macro invocation
+/// ```
+/// use proc_macro2::Span;
+/// use quote::quote;
+/// use rsmack_utils::{build_env, megamac::MacroKind};
+/// let env = build_env!(impls, Args, my_attr, Attr, attr = quote!(a = 1));
+/// assert_eq!(env.kind, MacroKind::Attr);
+/// assert_eq!(env.attr.to_string(), "a = 1");
+/// assert_eq!(format!("{:?}", env.call_site), format!("{:?}", Span::call_site()));
+/// let env = build_env!(impls, Args, my_func);
+/// assert_eq!(env.kind, MacroKind::Func);
+/// assert!(env.attr.is_empty());
+/// ```
 #[macro_export]
 macro_rules! build_env {
     (
         $implementations_mod_ident:ident,
         $exec_args_ident:ident,
         $exec_fn_mod_ident:ident
+    ) => {
+        rsmack_utils::build_env!(
+            $implementations_mod_ident,
+            $exec_args_ident,
+            $exec_fn_mod_ident,
+            Func
+        )
+    };
+    (
+        $implementations_mod_ident:ident,
+        $exec_args_ident:ident,
+        $exec_fn_mod_ident:ident,
+        $kind:ident
+        $(, attr = $attr_tok_stream:expr)?
     ) => {{
         // This can not be put in the builder otherwise the path is not the caller one
         let module_path = std::module_path!();
@@ -36,6 +265,9 @@ macro_rules! build_env {
             stringify!($exec_args_ident),
             stringify!($exec_fn_mod_ident),
         )
+        .kind(rsmack_utils::megamac::MacroKind::$kind)
+        $(.attr($attr_tok_stream))?
+        .call_site(proc_macro2::Span::call_site())
         .build()
     }};
 }
@@ -44,7 +276,13 @@ macro_rules! build_env {
 /// This macro is **proc-macro only**.
 /// Call a proc-macro implementation function named `exec`,
 /// located in the given `implementations module ident`,
-/// with the given `args type ident` and `type of the item` for [`syn::parse_macro_input`]
+/// with the given `args type ident` and `type of the item`.
+///
+/// Arguments and item are parsed with [`parse_attr_input`], reporting their errors together, optionally given `bare = <fn() -> Args>` and `primary = <field ident>`.
+/// The implementation function can be replaced with `exec = <fn(Args, item type, ExecEnv) -> TokenStream>`,
+/// e.g. a closure dispatching a [`syn::Item`] to several implementation functions.
+/// With `errors = syn`, the implementation function returns a [`syn::Result`] and is called with [`exec_syn`].
+/// The call is traced by [`DebugTrace`](crate::debug::DebugTrace) if enabled, the arguments being formatted if they implement [`Debug`]
 #[macro_export]
 macro_rules! call_attr_proc_macro {
     (
@@ -54,35 +292,87 @@ macro_rules! call_attr_proc_macro {
         $item_ty:ty,
         $attr_tok_stream:ident,
         $item_tok_stream:ident
+        $(, bare = $bare:expr)?
+        $(, primary = $primary:ident)?
+        $(, exec = $exec:expr)?
+        $(, errors = $errors:ident)?
     ) => {{
-        use darling::*;
-
-        let meta_list = match ast::NestedMeta::parse_meta_list($attr_tok_stream.into()) {
+        let attr_tok_stream = proc_macro2::TokenStream::from($attr_tok_stream);
+        let item_tok_stream = proc_macro2::TokenStream::from($item_tok_stream);
+        let (parsed_args, parsed_item) = match rsmack_utils::exec::parse_attr_input::<
+            crate::$implementations_mod_ident::$exec_fn_mod_ident::$exec_args_ident,
+            $item_ty,
+        >(
+            attr_tok_stream.clone(),
+            item_tok_stream.clone(),
+            None $(.or(Some($bare)))?,
+            None $(.or(Some(stringify!($primary))))?,
+        ) {
             Ok(v) => v,
             Err(e) => {
-                return proc_macro::TokenStream::from(Error::from(e).write_errors());
+                return proc_macro::TokenStream::from(e.write_errors());
             }
         };
-        let parsed_item = syn::parse_macro_input!($item_tok_stream as $item_ty);
-        let parsed_args =
-            match crate::$implementations_mod_ident::$exec_fn_mod_ident::$exec_args_ident::from_list(
-                &meta_list,
-            ) {
-                Ok(v) => v,
-                Err(e) => {
-                    return proc_macro::TokenStream::from(e.write_errors());
-                }
-            };
-        let env = rsmack_utils::build_env!($implementations_mod_ident, $exec_args_ident, $exec_fn_mod_ident);
-
-        crate::$implementations_mod_ident::$exec_fn_mod_ident::exec(
-            parsed_args,
-            parsed_item,
-            env
-        )
-        .into()
+        let env = rsmack_utils::build_env!(
+            $implementations_mod_ident,
+            $exec_args_ident,
+            $exec_fn_mod_ident,
+            Attr,
+            attr = attr_tok_stream
+        );
+        let trace = rsmack_utils::debug::DebugTrace::start(
+            &env,
+            &[("attr", &env.attr), ("item", &item_tok_stream)],
+            || rsmack_utils::debug::fmt_traced_args!(parsed_args),
+        );
+        let exec = rsmack_utils::exec::exec_fn_or!(
+            $($exec)?;
+            crate::$implementations_mod_ident::$exec_fn_mod_ident::exec
+        );
+        let (output, aborted) = rsmack_utils::exec::exec_with_errors!(
+            $($errors)?;
+            env,
+            |env| exec(parsed_args, parsed_item, env)
+        );
+        rsmack_utils::debug::DebugTrace::finish(trace, &output, aborted);
+        output.into()
     }};
 }
+/// The given implementation function if any, the default one otherwise
+#[doc(hidden)]
+#[macro_export]
+macro_rules! exec_fn_or {
+    (; $default:expr) => {
+        $default
+    };
+    ($exec:expr; $default:expr) => {
+        $exec
+    };
+}
+/// Call an implementation with the given error strategy, [`proc_macro_error2`] by default or `syn` (see [`exec_syn`]),
+/// along with whether it aborted, which is only returned by the `syn` strategy catching aborts
+#[doc(hidden)]
+#[macro_export]
+macro_rules! exec_with_errors {
+    ($(proc_macro_error2)?; $env:expr, $exec:expr) => {
+        ($exec($env), false)
+    };
+    (syn; $env:expr, $exec:expr) => {
+        rsmack_utils::exec::exec_syn_status($env, $exec)
+    };
+}
+/// Parse function-like proc-macro arguments with the given input kind, `Meta` by default or `Parse`
+/// (see [`parse_func_args`] and [`parse_func_syntax`])
+#[doc(hidden)]
+#[macro_export]
+macro_rules! parse_func_input {
+    ($(Meta)?; $args_ty:ty, $args_tok_stream:expr) => {
+        rsmack_utils::exec::parse_func_args::<$args_ty>($args_tok_stream)
+    };
+    (Parse; $args_ty:ty, $args_tok_stream:expr) => {
+        rsmack_utils::exec::parse_func_syntax::<$args_ty>($args_tok_stream)
+    };
+}
 /// Use [`call_func_proc_macro`] on `impls` mod with `Args` args ident
 #[macro_export]
 macro_rules! call_func_impls_with_args {
@@ -109,6 +399,11 @@ macro_rules! call_derive_impls_with_args {
     };
 }
 /// Call a function proc-macro implementation function named `exec`.
+///
+/// Arguments are parsed as a meta list with [`parse_func_args`], or with `input = Parse` as a custom syntax with [`parse_func_syntax`],
+/// the `args type ident` implementing [`syn::parse::Parse`].
+/// With `errors = syn`, the implementation function returns a [`syn::Result`] and is called with [`exec_syn`].
+/// The call is traced by [`DebugTrace`](crate::debug::DebugTrace) if enabled, the arguments being formatted if they implement [`Debug`]
 #[macro_export]
 macro_rules! call_func_proc_macro {
     (
@@ -116,35 +411,41 @@ macro_rules! call_func_proc_macro {
         $exec_args_ident:ident,
         $exec_fn_mod_ident:ident,
         $args_tok_stream:ident
+        $(, input = $input:ident)?
+        $(, errors = $errors:ident)?
     ) => {{
-        use darling::*;
-
-        let meta_list = match ast::NestedMeta::parse_meta_list($args_tok_stream.into()) {
+        let args_tok_stream = proc_macro2::TokenStream::from($args_tok_stream);
+        let parsed_args = match rsmack_utils::exec::parse_func_input!(
+            $($input)?;
+            crate::$implementations_mod_ident::$exec_fn_mod_ident::$exec_args_ident,
+            args_tok_stream.clone()
+        ) {
             Ok(v) => v,
             Err(e) => {
-                return proc_macro::TokenStream::from(Error::from(e).write_errors());
+                return proc_macro::TokenStream::from(e.write_errors());
             }
         };
-        let parsed_args =
-            match crate::$implementations_mod_ident::$exec_fn_mod_ident::$exec_args_ident::from_list(
-                &meta_list,
-            ) {
-                Ok(v) => v,
-                Err(e) => {
-                    return proc_macro::TokenStream::from(e.write_errors());
-                }
-            };
-        let env = rsmack_utils::build_env!($implementations_mod_ident, $exec_args_ident, $exec_fn_mod_ident);
-
-        crate::$implementations_mod_ident::$exec_fn_mod_ident::exec(
-            parsed_args,
-            env
-        )
-        .into()
+        let env = rsmack_utils::build_env!($implementations_mod_ident, $exec_args_ident, $exec_fn_mod_ident, Func);
+        let trace = rsmack_utils::debug::DebugTrace::start(
+            &env,
+            &[("args", &args_tok_stream)],
+            || rsmack_utils::debug::fmt_traced_args!(parsed_args),
+        );
+        let (output, aborted) = rsmack_utils::exec::exec_with_errors!(
+            $($errors)?;
+            env,
+            |env| crate::$implementations_mod_ident::$exec_fn_mod_ident::exec(parsed_args, env)
+        );
+        rsmack_utils::debug::DebugTrace::finish(trace, &output, aborted);
+        output.into()
     }};
 }
 
 /// Call a derive proc-macro implementation function named `exec`.
+///
+/// The derive input is parsed with the given `args type ident`, which must implement [`darling::FromDeriveInput`].
+/// With `errors = syn`, the implementation function returns a [`syn::Result`] and is called with [`exec_syn`].
+/// The call is traced by [`DebugTrace`](crate::debug::DebugTrace) if enabled, the arguments being formatted if they implement [`Debug`]
 #[macro_export]
 macro_rules! call_derive_proc_macro {
     (
@@ -152,15 +453,37 @@ macro_rules! call_derive_proc_macro {
         $exec_args_ident:ident,
         $exec_fn_mod_ident:ident,
         $item_tok_stream:ident
+        $(, errors = $errors:ident)?
     ) => {{
         use darling::*;
         let parsed_item = syn::parse_macro_input!($item_tok_stream as syn::DeriveInput);
+        let parsed_args =
+            match crate::$implementations_mod_ident::$exec_fn_mod_ident::$exec_args_ident::from_derive_input(
+                &parsed_item,
+            ) {
+                Ok(v) => v,
+                Err(e) => {
+                    return proc_macro::TokenStream::from(e.write_errors());
+                }
+            };
         let env = rsmack_utils::build_env!(
             $implementations_mod_ident,
             $exec_args_ident,
-            $exec_fn_mod_ident
+            $exec_fn_mod_ident,
+            Derive
+        );
+        let trace = rsmack_utils::debug::DebugTrace::start(
+            &env,
+            &[("item", &parsed_item)],
+            || rsmack_utils::debug::fmt_traced_args!(parsed_args),
+        );
+        let (output, aborted) = rsmack_utils::exec::exec_with_errors!(
+            $($errors)?;
+            env,
+            |env| crate::$implementations_mod_ident::$exec_fn_mod_ident::exec(parsed_args, env)
         );
-        crate::$implementations_mod_ident::$exec_fn_mod_ident::exec(parsed_item, env).into()
+        rsmack_utils::debug::DebugTrace::finish(trace, &output, aborted);
+        output.into()
     }};
 }
 
@@ -170,3 +493,9 @@ pub use call_derive_impls_with_args;
 pub use call_derive_proc_macro;
 pub use call_func_impls_with_args;
 pub use call_func_proc_macro;
+#[doc(hidden)]
+pub use exec_fn_or;
+#[doc(hidden)]
+pub use exec_with_errors;
+#[doc(hidden)]
+pub use parse_func_input;
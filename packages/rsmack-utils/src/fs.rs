@@ -94,6 +94,29 @@ pub fn folder_iso_struct(
     generate_file(format!("{name}.rs"), output.as_bytes());
 }
 
+/// List the modules of a flat folder of rust modules as the file stems of its `.rs` files, `mod` excluded,
+/// in directory listing order.
+/// Other files, directory modules (`dir/mod.rs`) and non UTF-8 file names are skipped.
+/// # Panics
+/// If the folder can not be read
+pub fn folder_modules(folder: &Path) -> Vec<String> {
+    std::fs::read_dir(folder)
+        .unwrap_or_else(|e| panic!("Failed to read modules folder {}: {e}", folder.display()))
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            if !entry.file_type().ok()?.is_file() {
+                return None;
+            }
+            let module_path = entry.path();
+            if module_path.extension()? != "rs" {
+                return None;
+            }
+            let file_stem = module_path.file_stem()?.to_str()?;
+            (file_stem != "mod").then(|| file_stem.to_string())
+        })
+        .collect()
+}
+
 fn parse_id_maybe_raw(s: &str) -> Ident {
     syn::parse_str::<Ident>(s).unwrap_or_else(|_| Ident::new_raw(s, Span::call_site()))
 }
@@ -110,10 +133,31 @@ pub fn generate_file<P: AsRef<Path>>(path: P, text: &[u8]) {
     let mut f = File::create(dest_path).unwrap();
     f.write_all(text).unwrap();
 }
+/// Get the package folder from `CARGO_MANIFEST_DIR` at runtime
+/// # Panics
+/// If `CARGO_MANIFEST_DIR` does not exist
+pub fn package_folder() -> PathBuf {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    PathBuf::from(manifest_dir)
+}
 /// Get the package source folder from `CARGO_MANIFEST_DIR` at runtime
 /// # Panics
 /// If `CARGO_MANIFEST_DIR` does not exist
 pub fn package_src_folder() -> PathBuf {
-    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
-    PathBuf::from(manifest_dir).join("src")
+    package_folder().join("src")
+}
+/// Get the cargo target folder at runtime, `CARGO_TARGET_DIR` if set,
+/// the `target` folder next to the nearest `Cargo.lock` of the package folder ancestors otherwise
+/// # Panics
+/// If `CARGO_MANIFEST_DIR` does not exist
+pub fn target_folder() -> PathBuf {
+    if let Ok(target_dir) = std::env::var("CARGO_TARGET_DIR") {
+        return PathBuf::from(target_dir);
+    }
+    let package_folder = package_folder();
+    package_folder
+        .ancestors()
+        .find(|folder| folder.join("Cargo.lock").exists())
+        .unwrap_or(&package_folder)
+        .join("target")
 }
@@ -1,6 +1,10 @@
+pub mod attrs;
+pub mod debug;
 pub mod doc;
 pub mod exec;
 pub mod fs;
 pub mod logr;
 pub mod megamac;
 pub mod schemars;
+#[cfg(feature = "testing")]
+pub mod testing;
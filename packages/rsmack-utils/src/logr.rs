@@ -1,6 +1,8 @@
 //! This module expose [`Logr`] a **proc-macro only** logger wrapper around [`proc_macro_error2`]
-use proc_macro2::Span;
-use std::fmt::Display;
+use proc_macro2::{Span, TokenStream};
+use proc_macro_error2::{Diagnostic, Level};
+use quote::quote_spanned;
+use std::{cell::RefCell, fmt::Display, rc::Rc};
 
 use crate::cl;
 #[derive(Debug, bon::Builder)]
@@ -8,20 +10,127 @@ use crate::cl;
 /// Logger around [`proc_macro_error2`], **only for proc-macros**
 pub struct Logr {
     pub prefix: String,
+    /// Messages recorded instead of being emitted, aborting by unwinding with [`LogrAbort`], used to test implementations outside of a proc-macro
+    pub captured: Option<Rc<RefCell<Vec<LogrMessage>>>>,
+    /// Messages collected until flushed, see [`Self::collecting`]
+    pub collected: Option<Rc<RefCell<Vec<LogrMessage>>>>,
 }
+/// Level of a [`LogrMessage`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogrLevel {
+    Error,
+    Warning,
+    /// Emitted as a warning, as [`proc_macro_error2`] only has notes attached to another diagnostic
+    Note,
+}
+/// Message recorded by a capturing or collecting [`Logr`]
+#[derive(Debug, Clone)]
+pub struct LogrMessage {
+    pub level: LogrLevel,
+    pub span: Span,
+    /// The message, prefixed with [`Logr::prefix`]
+    pub msg: String,
+    /// The help and note sub-diagnostics, see [`LogrDiagnostic`]
+    pub children: Vec<LogrChild>,
+}
+impl LogrMessage {
+    /// The message followed by its sub-diagnostics, one per line, for errors without sub-diagnostics support (e.g. `compile_error!`)
+    pub fn text(&self) -> String {
+        self.children.iter().fold(self.msg.clone(), |text, child| {
+            format!("{text}\n= {}: {}", child.kind, child.msg)
+        })
+    }
+}
+/// Kind of a [`LogrChild`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogrChildKind {
+    Help,
+    Note,
+}
+impl Display for LogrChildKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Help => "help",
+            Self::Note => "note",
+        })
+    }
+}
+/// Help or note attached to a [`LogrMessage`], optionally pointing at its own span
+#[derive(Debug, Clone)]
+pub struct LogrChild {
+    pub kind: LogrChildKind,
+    pub span: Option<Span>,
+    pub msg: String,
+}
+/// Error or warning built by [`Logr::error`] or [`Logr::warning`], with help and note sub-diagnostics
+/// mapped to the ones of [`proc_macro_error2::Diagnostic`]
+#[derive(Debug)]
+#[must_use = "A diagnostic does nothing unless emitted"]
+pub struct LogrDiagnostic<'a> {
+    logr: &'a Logr,
+    message: LogrMessage,
+}
+impl LogrDiagnostic<'_> {
+    fn child<M: Display>(mut self, kind: LogrChildKind, span: Option<Span>, msg: M) -> Self {
+        self.message.children.push(LogrChild {
+            kind,
+            span,
+            msg: msg.to_string(),
+        });
+        self
+    }
+    /// Attach a help message
+    pub fn help<M: Display>(self, msg: M) -> Self {
+        self.child(LogrChildKind::Help, None, msg)
+    }
+    /// Attach a help message pointing at `span`
+    pub fn help_at<M: Display>(self, span: Span, msg: M) -> Self {
+        self.child(LogrChildKind::Help, Some(span), msg)
+    }
+    /// Attach a note
+    pub fn note<M: Display>(self, msg: M) -> Self {
+        self.child(LogrChildKind::Note, None, msg)
+    }
+    /// Attach a note pointing at `span`
+    pub fn note_at<M: Display>(self, span: Span, msg: M) -> Self {
+        self.child(LogrChildKind::Note, Some(span), msg)
+    }
+    /// Emit the diagnostic, or record it with its sub-diagnostics if the [`Logr`] is collecting or capturing
+    pub fn emit(self) {
+        if let Some(message) = self.logr.record(self.message) {
+            self.logr.emit_message(message);
+        }
+    }
+    /// Emit the diagnostic and abort, see [`Logr::abort`]
+    pub fn abort(self) -> ! {
+        match self.logr.record(self.message) {
+            None => self.logr.recorded_abort(),
+            Some(message) => Logr::diagnostic(message).abort(),
+        }
+    }
+}
+/// Unwinding payload of an abort of a capturing [`Logr`]
+#[derive(Debug)]
+pub struct LogrAbort;
 macro_rules! emit_msg_with_span {
-    ($ident:ident, $ret:ty) => {
+    ($ident:ident, $ret:ty, $level:ident, $recorded_ret:expr) => {
         #[doc = concat!("Call ", cl!(proc_macro_error2::$ident!), " with ", cl!(Self::prefix))]
         pub fn $ident<M: AsRef<str> + Display>(&self, span: Span, msg: M) -> $ret {
-            proc_macro_error2::$ident!(span, self.fmt_msg(msg))
+            match self.record(self.message(LogrLevel::$level, span, msg)) {
+                None => $recorded_ret(self),
+                Some(message) => proc_macro_error2::$ident!(span, message.msg),
+            }
         }
     };
 }
 macro_rules! emit_msg {
-    ($ident:ident, $ret:ty) => {
+    ($ident:ident, $ret:ty, $level:ident, $recorded_ret:expr) => {
         #[doc = concat!("Call ", cl!(proc_macro_error2::$ident!), " with ", cl!(Self::prefix))]
         pub fn $ident<M: AsRef<str> + Display>(&self, msg: M) -> $ret {
-            proc_macro_error2::$ident!(self.fmt_msg(msg))
+            match self.record(self.message(LogrLevel::$level, Span::call_site(), msg)) {
+                None => $recorded_ret(self),
+                Some(message) => proc_macro_error2::$ident!(message.msg),
+            }
         }
     };
 }
@@ -29,10 +138,203 @@ impl Logr {
     fn fmt_msg<M: AsRef<str> + Display>(&self, msg: M) -> String {
         format!("#[{}] {}", self.prefix, msg)
     }
-    emit_msg_with_span! {abort, !}
-    emit_msg_with_span! {emit_error, ()}
-    emit_msg_with_span! {emit_warning, ()}
-    emit_msg! {abort_call_site, !}
-    emit_msg! {emit_call_site_error, ()}
-    emit_msg! {emit_call_site_warning, ()}
+    /// A message prefixed with [`Self::prefix`]
+    fn message<M: AsRef<str> + Display>(
+        &self,
+        level: LogrLevel,
+        span: Span,
+        msg: M,
+    ) -> LogrMessage {
+        LogrMessage {
+            level,
+            span,
+            msg: self.fmt_msg(msg),
+            children: vec![],
+        }
+    }
+    /// Record the message if collecting or capturing, giving it back otherwise
+    fn record(&self, message: LogrMessage) -> Option<LogrMessage> {
+        let Some(recorded) = self.collected.as_ref().or(self.captured.as_ref()) else {
+            return Some(message);
+        };
+        recorded.borrow_mut().push(message);
+        None
+    }
+    /// The [`proc_macro_error2`] diagnostic of a message and its sub-diagnostics
+    fn diagnostic(message: LogrMessage) -> Diagnostic {
+        let LogrMessage {
+            level,
+            span,
+            msg,
+            children,
+        } = message;
+        let diagnostic = match level {
+            LogrLevel::Error => Diagnostic::spanned(span, Level::Error, msg),
+            LogrLevel::Warning => Diagnostic::spanned(span, Level::Warning, msg),
+            LogrLevel::Note => Diagnostic::spanned(span, Level::Warning, format!("note: {msg}")),
+        };
+        children.into_iter().fold(diagnostic, |diagnostic, child| {
+            match (child.kind, child.span) {
+                (LogrChildKind::Help, None) => diagnostic.help(child.msg),
+                (LogrChildKind::Help, Some(span)) => diagnostic.span_help(span, child.msg),
+                (LogrChildKind::Note, None) => diagnostic.note(child.msg),
+                (LogrChildKind::Note, Some(span)) => diagnostic.span_note(span, child.msg),
+            }
+        })
+    }
+    /// Emit an already prefixed message, recording it if capturing
+    fn emit_message(&self, message: LogrMessage) {
+        if let Some(captured) = &self.captured {
+            captured.borrow_mut().push(message);
+            return;
+        }
+        Self::diagnostic(message).emit();
+    }
+    /// Build an error with help and note sub-diagnostics, see [`LogrDiagnostic`]
+    /// ```
+    /// use proc_macro2::Span;
+    /// use rsmack_utils::logr::Logr;
+    /// let logr = Logr::builder().prefix("my_macro").build().collecting();
+    /// logr.error(Span::call_site(), "Unsupported type")
+    ///     .help("use a path type")
+    ///     .note_at(Span::call_site(), "required by this attribute")
+    ///     .emit();
+    /// let errors = logr.flush().to_string();
+    /// assert!(errors.contains(r"#[my_macro] Unsupported type\n= help: use a path type\n= note: required by this attribute"));
+    /// ```
+    pub fn error<M: AsRef<str> + Display>(&self, span: Span, msg: M) -> LogrDiagnostic<'_> {
+        LogrDiagnostic {
+            logr: self,
+            message: self.message(LogrLevel::Error, span, msg),
+        }
+    }
+    /// Build a warning with help and note sub-diagnostics, see [`LogrDiagnostic`]
+    pub fn warning<M: AsRef<str> + Display>(&self, span: Span, msg: M) -> LogrDiagnostic<'_> {
+        LogrDiagnostic {
+            logr: self,
+            message: self.message(LogrLevel::Warning, span, msg),
+        }
+    }
+    /// Take the collected messages, if collecting
+    fn take_collected(&self) -> Vec<LogrMessage> {
+        self.collected
+            .as_ref()
+            .map(|collected| collected.take())
+            .unwrap_or_default()
+    }
+    /// A [`Logr`] with the same prefix collecting its messages instead of emitting them,
+    /// to report every error of an item before failing with [`Self::flush`] or [`Self::flush_darling`].
+    ///
+    /// Aborting emits the collected messages before aborting, collected messages not flushed are lost.
+    /// ```
+    /// use proc_macro2::Span;
+    /// use rsmack_utils::logr::Logr;
+    /// let logr = Logr::builder().prefix("my_macro").build().collecting();
+    /// assert!(!logr.has_errors());
+    /// for field in ["a", "b"] {
+    ///     logr.emit_error(Span::call_site(), format!("Unsupported field `{field}`"));
+    /// }
+    /// assert!(logr.has_errors());
+    /// let errors = logr.flush();
+    /// assert_eq!(errors.to_string().matches("compile_error").count(), 2);
+    /// assert!(!logr.has_errors());
+    /// ```
+    #[must_use]
+    pub fn collecting(&self) -> Self {
+        Self {
+            prefix: self.prefix.clone(),
+            captured: self.captured.clone(),
+            collected: Some(Rc::default()),
+        }
+    }
+    /// Whether errors were collected since the last flush, always `false` when not collecting (see [`Self::collecting`]),
+    /// even if the errors are captured by a test
+    /// ```
+    /// use proc_macro2::Span;
+    /// use rsmack_utils::logr::Logr;
+    /// let logr = Logr::builder().prefix("my_macro").captured(Default::default()).build();
+    /// logr.emit_error(Span::call_site(), "Captured");
+    /// assert!(!logr.has_errors());
+    /// let collecting = logr.collecting();
+    /// collecting.emit_error(Span::call_site(), "Collected");
+    /// assert!(collecting.has_errors());
+    /// ```
+    pub fn has_errors(&self) -> bool {
+        self.collected.as_ref().is_some_and(|collected| {
+            collected
+                .borrow()
+                .iter()
+                .any(|m| m.level == LogrLevel::Error)
+        })
+    }
+    /// Emit the collected messages, see [`Self::collecting`]
+    pub fn emit_collected(&self) {
+        for message in self.take_collected() {
+            self.emit_message(message);
+        }
+    }
+    /// Convert the collected errors to `compile_error!` invocations, to be returned as (or along with) the macro output,
+    /// warnings and notes being emitted. See [`Self::collecting`]
+    pub fn flush(&self) -> TokenStream {
+        let mut errors = TokenStream::new();
+        for message in self.take_collected() {
+            if message.level == LogrLevel::Error {
+                let span = message.span;
+                let text = message.text();
+                errors.extend(quote_spanned!(span=> ::core::compile_error!{ #text }));
+            } else {
+                self.emit_message(message);
+            }
+        }
+        errors
+    }
+    /// Convert the collected errors to a [`darling::Error::multiple`], warnings and notes being emitted. See [`Self::collecting`]
+    /// # Errors
+    /// If errors were collected
+    /// ```
+    /// use proc_macro2::Span;
+    /// use rsmack_utils::logr::Logr;
+    /// let logr = Logr::builder().prefix("my_macro").build().collecting();
+    /// logr.emit_error(Span::call_site(), "first");
+    /// logr.emit_error(Span::call_site(), "second");
+    /// assert_eq!(logr.flush_darling().unwrap_err().len(), 2);
+    /// assert!(logr.flush_darling().is_ok());
+    /// ```
+    pub fn flush_darling(&self) -> darling::Result<()> {
+        let mut errors = darling::Error::accumulator();
+        for message in self.take_collected() {
+            if message.level == LogrLevel::Error {
+                errors.push(syn::Error::new(message.span, message.text()).into());
+            } else {
+                self.emit_message(message);
+            }
+        }
+        errors.finish()
+    }
+    /// Build a [`syn::Error`] with [`Self::prefix`], returned by implementations of the `syn` error strategy (see [`exec_syn`](crate::exec::exec_syn))
+    pub fn syn_error<M: AsRef<str> + Display>(&self, span: Span, msg: M) -> syn::Error {
+        syn::Error::new(span, self.fmt_msg(msg))
+    }
+    /// Abort once the abort message is recorded: emit the collected messages then unwind with [`LogrAbort`] if capturing,
+    /// abort through [`proc_macro_error2`] otherwise
+    fn recorded_abort(&self) -> ! {
+        self.emit_collected();
+        if self.captured.is_some() {
+            std::panic::resume_unwind(Box::new(LogrAbort))
+        }
+        proc_macro_error2::abort_if_dirty();
+        unreachable!("An error was emitted before aborting")
+    }
+    /// Emit a note, as a warning outside of a collecting or capturing [`Logr`] (see [`LogrLevel::Note`])
+    pub fn emit_note<M: AsRef<str> + Display>(&self, span: Span, msg: M) {
+        if let Some(message) = self.record(self.message(LogrLevel::Note, span, msg)) {
+            self.emit_message(message);
+        }
+    }
+    emit_msg_with_span! {abort, !, Error, Self::recorded_abort}
+    emit_msg_with_span! {emit_error, (), Error, |_| ()}
+    emit_msg_with_span! {emit_warning, (), Warning, |_| ()}
+    emit_msg! {abort_call_site, !, Error, Self::recorded_abort}
+    emit_msg! {emit_call_site_error, (), Error, |_| ()}
+    emit_msg! {emit_call_site_warning, (), Warning, |_| ()}
 }
@@ -1,7 +1,15 @@
 //! [rsmack-megamac](../rsmack_megamac/index.html) related utils
+use std::{fmt::Display, str::FromStr};
+
 use bon::Builder;
+use proc_macro2::{Span, TokenStream};
 
 use crate::logr::Logr;
+
+pub mod args_doc;
+pub mod facade;
+pub mod impl_file;
+pub mod manifest;
 #[derive(Debug, Builder)]
 #[builder(on(String, into))]
 pub struct ExecEnv {
@@ -18,4 +26,51 @@ pub struct ExecEnv {
         .build())
     ]
     pub logr: Logr,
+    /// The kind of the invoked macro, [`MacroKind::Func`] if not given
+    #[builder(default)]
+    pub kind: MacroKind,
+    /// The arguments of an attribute macro as given, e.g. `a = 1` for `#[name(a = 1)]`, empty for other kinds
+    #[builder(default)]
+    pub attr: TokenStream,
+    /// The span of the macro invocation, the attribute of an attribute macro rather than its item (see [`Span::call_site`])
+    #[builder(default = Span::call_site())]
+    pub call_site: Span,
+}
+
+/// The kind of a procedural macro
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MacroKind {
+    /// Function-like procedural macro (e.g., `my_macro!()`)
+    #[default]
+    Func,
+    /// Attribute procedural macro (e.g., `#[my_macro]`)
+    Attr,
+    /// Derive procedural macro (e.g., `#[derive(MyMacro)]`)
+    Derive,
+}
+
+impl Display for MacroKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Func => "Func",
+            Self::Attr => "Attr",
+            Self::Derive => "Derive",
+        })
+    }
+}
+
+impl FromStr for MacroKind {
+    type Err = String;
+
+    /// Parse `Func`, `Attr` or `Derive`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Func" => Ok(Self::Func),
+            "Attr" => Ok(Self::Attr),
+            "Derive" => Ok(Self::Derive),
+            _ => Err(format!(
+                "Unsupported macro kind `{s}`, expected one of `Func`, `Attr`, `Derive`"
+            )),
+        }
+    }
 }
@@ -0,0 +1,444 @@
+//! Documentation of the parameters of a [rsmack-megamac](../../../rsmack_megamac/index.html) implementation `Args` struct
+use bon::Builder;
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+use syn::{meta::ParseNestedMeta, *};
+
+/// Documentation of a field of a macro arguments struct, as a macro parameter
+#[derive(Debug, Clone, Builder, Serialize, Deserialize)]
+#[builder(on(String, into))]
+pub struct FieldDoc {
+    /// The field identifier
+    pub ident: String,
+    /// The parameter name, the field identifier unless renamed with `#[darling(rename = "...")]`
+    pub name: String,
+    /// The field documentation, doc comment lines joined
+    pub doc: Option<String>,
+    /// The field type, formatted without spaces
+    pub ty: String,
+    /// Whether the parameter may be omitted, either an [`Option`], a `#[darling(default)]` or a `#[darling(multiple)]` field
+    #[builder(default)]
+    pub optional: bool,
+    /// The default value of an omitted parameter, when not [`None`]
+    pub default: Option<String>,
+    /// Whether the parameter may be repeated, a `#[darling(multiple)]` field
+    #[builder(default)]
+    pub multiple: bool,
+    /// Whether the parameters of the field type are inlined, a `#[darling(flatten)]` field
+    #[builder(default)]
+    pub flatten: bool,
+    /// The parameters of the field type, when it is a `FromMeta` struct of the same file
+    #[builder(default)]
+    pub nested: Vec<FieldDoc>,
+    /// The values allowed for the field type as written in the macro input, when it is a `FromMeta` enum of the same file
+    #[builder(default)]
+    pub allowed_values: Vec<String>,
+}
+
+impl FieldDoc {
+    /// Build the documentation of a named field, [`None`] for unnamed and `#[darling(skip)]` fields
+    pub fn from_field(field: &Field) -> Option<Self> {
+        let ident = field.ident.clone()?;
+        let doc_lines = doc_lines(&field.attrs);
+        let doc = (!doc_lines.is_empty()).then(|| doc_lines.join("\n"));
+
+        let mut darling_opts = DarlingFieldOpts::default();
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("darling")) {
+            // Unknown or malformed darling options are left to darling to report
+            let _ = attr.parse_nested_meta(|meta| darling_opts.parse_nested_meta(&meta));
+        }
+        if darling_opts.skip {
+            return None;
+        }
+
+        let is_option = match &field.ty {
+            Type::Path(TypePath { path, .. }) => {
+                path.segments.last().is_some_and(|s| s.ident == "Option")
+            }
+            _ => false,
+        };
+        Some(
+            FieldDoc::builder()
+                .name(darling_opts.rename.unwrap_or_else(|| ident.to_string()))
+                .ident(ident.to_string())
+                .maybe_doc(doc)
+                .ty(field.ty.to_token_stream().to_string().replace(' ', ""))
+                .optional(is_option || darling_opts.default.is_some() || darling_opts.multiple)
+                .maybe_default(darling_opts.default.filter(|_| !is_option))
+                .multiple(darling_opts.multiple)
+                .flatten(darling_opts.flatten)
+                .build(),
+        )
+    }
+
+    /// Format as a markdown list item
+    /// ```
+    /// use rsmack_utils::megamac::args_doc::args_fields_doc;
+    /// let args: syn::ItemStruct = syn::parse_quote! {
+    ///     struct Args {
+    ///         /// The type with which to wrap
+    ///         /// the struct item fields
+    ///         #[darling(default, rename = "wrapper")]
+    ///         with: syn::Ident,
+    ///         #[darling(multiple)]
+    ///         derive: Vec<syn::Path>,
+    ///     }
+    /// };
+    /// let fields_doc = args_fields_doc(&args, &syn::parse_quote!());
+    /// assert_eq!(
+    ///     fields_doc[0].to_markdown(),
+    ///     "* `wrapper` - The type with which to wrap\n  the struct item fields\n  + type: [`syn::Ident`]\n  + optional, defaults to `Default::default()`\n  + renamed from field `with`"
+    /// );
+    /// assert_eq!(
+    ///     fields_doc[1].to_markdown(),
+    ///     "* `derive` - Not documented\n  + type: [`Vec<syn::Path>`]\n  + optional\n  + may be repeated, e.g. `derive = ..., derive = ...`"
+    /// );
+    /// ```
+    pub fn to_markdown(&self) -> String {
+        self.to_nested_markdown("", 0)
+    }
+
+    /// Format as a markdown list item of the given nesting depth, the name being prefixed by the outer parameters names
+    fn to_nested_markdown(&self, name_prefix: &str, depth: usize) -> String {
+        let indent = "    ".repeat(depth);
+        let name = format!("{name_prefix}{}", self.name);
+        let mut lines = vec![
+            format!(
+                "{indent}* `{name}` - {}",
+                self.doc
+                    .as_deref()
+                    .unwrap_or("Not documented")
+                    // Keep continuation lines in the markdown list item
+                    .replace('\n', &format!("\n{indent}  "))
+            ),
+            format!("{indent}  + type: [`{}`]", self.ty),
+        ];
+        if self.optional {
+            lines.push(match &self.default {
+                Some(default) => format!("{indent}  + optional, defaults to `{default}`"),
+                None => format!("{indent}  + optional"),
+            });
+        }
+        if self.multiple {
+            lines.push(format!(
+                "{indent}  + may be repeated, e.g. `{0} = ..., {0} = ...`",
+                self.name
+            ));
+        }
+        if self.ident != self.name {
+            lines.push(format!("{indent}  + renamed from field `{}`", self.ident));
+        }
+        if !self.allowed_values.is_empty() {
+            lines.push(format!(
+                "{indent}  + allowed values: {}",
+                self.allowed_values
+                    .iter()
+                    .map(|v| format!("`{v}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !self.nested.is_empty() {
+            lines.push(format!("{indent}  + parameters:"));
+            let nested_prefix = format!("{name}.");
+            lines.extend(
+                self.nested
+                    .iter()
+                    .map(|fd| fd.to_nested_markdown(&nested_prefix, depth + 1)),
+            );
+        }
+        lines.join("\n")
+    }
+}
+
+/// The `#[darling(...)]` field options relevant to the documentation
+#[derive(Debug, Default)]
+struct DarlingFieldOpts {
+    default: Option<String>,
+    rename: Option<String>,
+    multiple: bool,
+    skip: bool,
+    flatten: bool,
+}
+
+impl DarlingFieldOpts {
+    fn parse_nested_meta(&mut self, meta: &ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("default") {
+            self.default = Some(if meta.input.peek(Token![=]) {
+                match meta.value()?.parse::<Expr>()? {
+                    // A path to a function, possibly quoted
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(lit_str),
+                        ..
+                    }) => format!("{}()", lit_str.value()),
+                    Expr::Path(path) => format!("{}()", path.to_token_stream()).replace(' ', ""),
+                    expr => expr.to_token_stream().to_string(),
+                }
+            } else {
+                "Default::default()".into()
+            });
+        } else if meta.path.is_ident("rename") {
+            self.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+        } else if meta.path.is_ident("multiple") {
+            self.multiple = true;
+        } else if meta.path.is_ident("skip") {
+            self.skip = true;
+        } else if meta.path.is_ident("flatten") {
+            self.flatten = true;
+        } else {
+            skip_nested_meta(meta)?;
+        }
+        Ok(())
+    }
+}
+
+/// Skip a nested meta value, if any, either `= <expr>` or `(...)`
+/// # Errors
+/// If the value is not an expression nor a parenthesized group
+pub fn skip_nested_meta(meta: &ParseNestedMeta) -> Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>().map(|_| ())
+    } else if meta.input.peek(token::Paren) {
+        meta.input.parse::<proc_macro2::TokenTree>().map(|_| ())
+    } else {
+        Ok(())
+    }
+}
+
+/// Collect the `#[doc = "..."]` lines of attributes, without the leading space of doc comments
+pub fn doc_lines(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter_map(|a| match &a.meta {
+            Meta::NameValue(MetaNameValue {
+                path,
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(lit_str),
+                        ..
+                    }),
+                ..
+            }) if path.is_ident("doc") => Some(lit_str.value()),
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.lines()
+                .map(|line| {
+                    line.strip_prefix(' ')
+                        .unwrap_or(line)
+                        .trim_end()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Extract the `# Examples` (or `# Example`) section of the documentation of a macro arguments struct, heading excluded
+/// ```
+/// use rsmack_utils::megamac::args_doc::args_examples_doc;
+/// let args: syn::ItemStruct = syn::parse_quote! {
+///     /// Arguments of `wrap`
+///     ///
+///     /// # Examples
+///     /// ```
+///     /// # use rsmack_wrap::wrap;
+///     /// #[wrap(with = Option)]
+///     /// struct A { a: u8 }
+///     /// ```
+///     /// # Panics
+///     /// Never
+///     struct Args {}
+/// };
+/// assert_eq!(
+///     args_examples_doc(&args).unwrap(),
+///     "```\n# use rsmack_wrap::wrap;\n#[wrap(with = Option)]\nstruct A { a: u8 }\n```"
+/// );
+/// ```
+pub fn args_examples_doc(args_struct: &ItemStruct) -> Option<String> {
+    let lines = doc_lines(&args_struct.attrs);
+    let start = lines
+        .iter()
+        .position(|l| l == "# Examples" || l == "# Example")?;
+    let mut in_code_block = false;
+    let section = lines[start + 1..]
+        .iter()
+        .take_while(|l| {
+            if l.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+            }
+            // Hidden lines of code blocks also start with `# `
+            in_code_block || !l.starts_with("# ")
+        })
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let section = section.trim();
+    (!section.is_empty()).then(|| section.to_string())
+}
+
+/// Whether the attributes derive the given trait, compared on the last path segment
+pub fn derives(attrs: &[Attribute], trait_ident: &str) -> bool {
+    derived_traits(attrs)
+        .iter()
+        .any(|derive| derive == trait_ident)
+}
+
+/// The last path segment of the traits derived by the attributes
+pub fn derived_traits(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|a| a.path().is_ident("derive"))
+        .filter_map(|a| {
+            a.parse_args_with(punctuated::Punctuated::<Path, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .filter_map(|derive| derive.segments.last().map(|s| s.ident.to_string()))
+        .collect()
+}
+
+/// Build the documentation of the fields of a macro arguments struct.
+///
+/// Field types which are `FromMeta` structs or enums of the same file are resolved, through [`Option`], [`Vec`] and [`Box`],
+/// to document their parameters or allowed values. `#[darling(flatten)]` fields parameters are inlined.
+/// ```
+/// use rsmack_utils::megamac::args_doc::args_fields_doc;
+/// let file: syn::File = syn::parse_quote! {
+///     #[derive(FromMeta)]
+///     struct Args {
+///         /// Where to pull constants from
+///         source: Source,
+///     }
+///     #[derive(FromMeta)]
+///     struct Source {
+///         /// The module path
+///         path: syn::Path,
+///         #[darling(default)]
+///         mode: Option<Mode>,
+///     }
+///     #[derive(FromMeta)]
+///     enum Mode { Strict, #[darling(rename = "lax")] Lenient, SkipEmpty }
+/// };
+/// let syn::Item::Struct(args) = &file.items[0] else { unreachable!() };
+/// assert_eq!(
+///     args_fields_doc(args, &file)[0].to_markdown(),
+///     "* `source` - Where to pull constants from
+///   + type: [`Source`]
+///   + parameters:
+///     * `source.path` - The module path
+///       + type: [`syn::Path`]
+///     * `source.mode` - Not documented
+///       + type: [`Option<Mode>`]
+///       + optional
+///       + allowed values: `\"strict\"`, `\"lax\"`, `\"skip_empty\"`"
+/// );
+/// ```
+pub fn args_fields_doc(args_struct: &ItemStruct, file: &File) -> Vec<FieldDoc> {
+    struct_fields_doc(args_struct, file, &mut vec![args_struct.ident.clone()])
+}
+
+/// Build the documentation of the fields of a struct, `visiting` holding the types being documented to stop on recursive types
+fn struct_fields_doc(
+    item_struct: &ItemStruct,
+    file: &File,
+    visiting: &mut Vec<Ident>,
+) -> Vec<FieldDoc> {
+    item_struct
+        .fields
+        .iter()
+        .filter_map(|field| Some((field, FieldDoc::from_field(field)?)))
+        .flat_map(|(field, mut fd)| {
+            match find_from_meta_item(&field.ty, file) {
+                Some(Item::Struct(nested)) if !visiting.contains(&nested.ident) => {
+                    visiting.push(nested.ident.clone());
+                    fd.nested = struct_fields_doc(nested, file, visiting);
+                    visiting.pop();
+                }
+                Some(Item::Enum(item_enum)) => fd.allowed_values = enum_allowed_values(item_enum),
+                _ => {}
+            }
+            if fd.flatten && !fd.nested.is_empty() {
+                std::mem::take(&mut fd.nested)
+            } else {
+                vec![fd]
+            }
+        })
+        .collect()
+}
+
+/// Find the `FromMeta` struct or enum of the file named as the type, looking through [`Option`], [`Vec`] and [`Box`]
+fn find_from_meta_item<'a>(ty: &Type, file: &'a File) -> Option<&'a Item> {
+    let Type::Path(TypePath { path, .. }) = ty else {
+        return None;
+    };
+    let segment = path.segments.last()?;
+    if let PathArguments::AngleBracketed(generic_args) = &segment.arguments {
+        if ["Option", "Vec", "Box"].contains(&segment.ident.to_string().as_str()) {
+            return match generic_args.args.first() {
+                Some(GenericArgument::Type(inner_ty)) => find_from_meta_item(inner_ty, file),
+                _ => None,
+            };
+        }
+    }
+    file.items.iter().find(|item| match item {
+        Item::Struct(ItemStruct { ident, attrs, .. })
+        | Item::Enum(ItemEnum { ident, attrs, .. }) => {
+            *ident == segment.ident && derives(attrs, "FromMeta")
+        }
+        _ => false,
+    })
+}
+
+/// The values allowed for a `FromMeta` enum, following darling variant renaming (`snake_case` by default)
+fn enum_allowed_values(item_enum: &ItemEnum) -> Vec<String> {
+    let mut rename_all = None;
+    for attr in item_enum
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("darling"))
+    {
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                rename_all = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                skip_nested_meta(&meta)
+            }
+        });
+    }
+    item_enum
+        .variants
+        .iter()
+        .filter_map(|variant| {
+            let mut darling_opts = DarlingFieldOpts::default();
+            for attr in variant
+                .attrs
+                .iter()
+                .filter(|a| a.path().is_ident("darling"))
+            {
+                let _ = attr.parse_nested_meta(|meta| darling_opts.parse_nested_meta(&meta));
+            }
+            if darling_opts.skip {
+                return None;
+            }
+            let value = darling_opts.rename.unwrap_or_else(|| {
+                let variant_name = variant.ident.to_string();
+                match rename_all.as_deref() {
+                    Some("lowercase") => variant_name.to_lowercase(),
+                    Some("UPPERCASE") => variant_name.to_uppercase(),
+                    Some("PascalCase") => stringcase::pascal_case(&variant_name),
+                    Some("camelCase") => stringcase::camel_case(&variant_name),
+                    Some("SCREAMING_SNAKE_CASE") => stringcase::macro_case(&variant_name),
+                    Some("kebab-case") => stringcase::kebab_case(&variant_name),
+                    Some("none") => variant_name,
+                    _ => stringcase::snake_case(&variant_name),
+                }
+            });
+            Some(match variant.fields {
+                Fields::Unit => format!("{value:?}"),
+                _ => format!("{value}(...)"),
+            })
+        })
+        .collect()
+}
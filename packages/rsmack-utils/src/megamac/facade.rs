@@ -0,0 +1,47 @@
+//! `macro_rules!` facades of function-like proc-macros, declared in the crates re-exporting them
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse::Parser, Path};
+
+/// Build a `#[macro_export]` `macro_rules!` named as the proc-macro, forwarding its tokens to it through `$crate`.
+///
+/// The input is the path, relative to the re-exporting crate root, of the proc-macro crate re-export,
+/// the proc-macro crate name if empty (i.e. `pub use <crate_name>;` at the root).
+/// # Errors
+/// If the input is not a path
+/// ```
+/// use quote::quote;
+/// use rsmack_utils::megamac::facade::macro_rules_facade;
+/// assert_eq!(
+///     macro_rules_facade("foo", "foo_macros", quote!()).unwrap().to_string(),
+///     quote! {
+///         #[macro_export]
+///         macro_rules! foo {
+///             ($($tokens:tt)*) => { $crate::foo_macros::foo! { $($tokens)* } };
+///         }
+///     }
+///     .to_string()
+/// );
+/// assert!(macro_rules_facade("foo", "foo_macros", quote!(__private::foo_macros)).is_ok());
+/// assert!(macro_rules_facade("foo", "foo_macros", quote!(1)).is_err());
+/// ```
+pub fn macro_rules_facade(
+    name: &str,
+    crate_name: &str,
+    input: TokenStream,
+) -> syn::Result<TokenStream> {
+    let reexport = if input.is_empty() {
+        let crate_ident = format_ident!("{crate_name}");
+        quote!(#crate_ident)
+    } else {
+        let path = Path::parse_mod_style.parse2(input)?;
+        quote!(#path)
+    };
+    let name = format_ident!("{name}");
+    Ok(quote! {
+        #[macro_export]
+        macro_rules! #name {
+            ($($tokens:tt)*) => { $crate::#reexport::#name! { $($tokens)* } };
+        }
+    })
+}
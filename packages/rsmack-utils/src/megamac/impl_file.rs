@@ -0,0 +1,310 @@
+//! Summary of a [rsmack-megamac](../../../rsmack_megamac/index.html) implementation file.
+//!
+//! A parsed [`syn::File`] holds compiler handles only valid during one macro invocation,
+//! so the data megamac reads from the file is extracted as plain data, which can be cached across invocations
+//! and embedded at build time with [`embed_impl_files`].
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, Mutex, MutexGuard, PoisonError},
+    time::SystemTime,
+};
+
+use bon::builder;
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+use syn::*;
+
+use super::args_doc::{
+    args_examples_doc, args_fields_doc, derived_traits, skip_nested_meta, FieldDoc,
+};
+use crate::fs::{folder_modules, package_src_folder};
+
+/// The plain data read from a macro implementation file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImplFileSummary {
+    /// The `exec` and `exec_<receiver>` functions
+    pub exec_fns: Vec<ExecFnSummary>,
+    /// The arguments struct, [`None`] if missing
+    pub args_struct: Option<ArgsStructSummary>,
+    /// The helper attributes read by a `#[darling(attributes(..))]` of a struct or enum of the file
+    pub read_helper_attrs: Vec<String>,
+}
+
+/// The signature of an implementation `exec` function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecFnSummary {
+    /// The function name
+    pub name: String,
+    /// Whether the function is `pub`
+    pub public: bool,
+    /// The parameter types, formatted without spaces
+    pub params: Vec<String>,
+    /// The return type, formatted without spaces, `()` if omitted
+    pub output: String,
+}
+
+impl ExecFnSummary {
+    /// Whether the function returns a `Result`, as implementations of the `syn` error strategy
+    /// (see [`exec_syn`](crate::exec::exec_syn))
+    pub fn returns_result(&self) -> bool {
+        let output = self.output.split('<').next().unwrap_or_default();
+        output.rsplit("::").next() == Some("Result")
+    }
+}
+
+/// An implementation arguments struct
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArgsStructSummary {
+    /// The documentation of the fields, see [`args_fields_doc`]
+    pub fields_doc: Vec<FieldDoc>,
+    /// The `# Examples` section of the struct documentation, see [`args_examples_doc`]
+    pub examples_doc: Option<String>,
+    /// The traits the struct derives or the file implements for it, by last path segment
+    pub traits: Vec<String>,
+}
+
+impl ArgsStructSummary {
+    /// Whether the struct derives or the file implements the trait, compared on the last path segment,
+    /// e.g. [`darling::FromDeriveInput`], [`Default`] or [`syn::parse::Parse`]
+    pub fn implements(&self, trait_ident: &str) -> bool {
+        self.traits.iter().any(|t| t == trait_ident)
+    }
+}
+
+/// Parsed summaries by file path and arguments struct name, with the file modification time they were parsed at
+type SummariesCache = HashMap<(PathBuf, String), (SystemTime, Arc<ImplFileSummary>)>;
+
+static CACHE: LazyLock<Mutex<SummariesCache>> = LazyLock::new(Mutex::default);
+
+/// Lock the summaries cache, a panic while holding it leaving it usable
+fn cache() -> MutexGuard<'static, SummariesCache> {
+    CACHE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl ImplFileSummary {
+    /// Summarize an implementation file which arguments struct is named `exec_args_ident`
+    /// ```
+    /// use rsmack_utils::megamac::impl_file::ImplFileSummary;
+    /// let summary = ImplFileSummary::parse(
+    ///     "#[derive(Default, FromMeta)] pub struct Args { a: u8 } pub fn exec(args: Args, env: ExecEnv) -> TokenStream { todo!() }",
+    ///     "Args",
+    /// ).unwrap();
+    /// let exec_fn = summary.exec_fn("exec").unwrap();
+    /// assert_eq!(exec_fn.params, ["Args", "ExecEnv"]);
+    /// assert!(!exec_fn.returns_result());
+    /// let args_struct = summary.args_struct.unwrap();
+    /// assert!(args_struct.implements("Default") && !args_struct.implements("FromDeriveInput"));
+    /// assert_eq!(args_struct.fields_doc[0].name, "a");
+    /// ```
+    /// # Errors
+    /// If the source is not a valid Rust file
+    pub fn parse(src: &str, exec_args_ident: &str) -> Result<Self> {
+        Ok(Self::from_file(&parse_file(src)?, exec_args_ident))
+    }
+
+    /// Summarize a parsed implementation file which arguments struct is named `exec_args_ident`
+    pub fn from_file(file: &File, exec_args_ident: &str) -> Self {
+        let exec_fns = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Fn(item_fn)
+                    if item_fn.sig.ident == "exec"
+                        || item_fn.sig.ident.to_string().starts_with("exec_") =>
+                {
+                    Some(ExecFnSummary {
+                        name: item_fn.sig.ident.to_string(),
+                        public: matches!(item_fn.vis, Visibility::Public(_)),
+                        params: item_fn
+                            .sig
+                            .inputs
+                            .iter()
+                            .map(|input| match input {
+                                FnArg::Typed(PatType { ty, .. }) => {
+                                    ty.to_token_stream().to_string().replace(' ', "")
+                                }
+                                FnArg::Receiver(receiver) => receiver.to_token_stream().to_string(),
+                            })
+                            .collect(),
+                        output: match &item_fn.sig.output {
+                            ReturnType::Default => "()".to_string(),
+                            ReturnType::Type(_, ty) => {
+                                ty.to_token_stream().to_string().replace(' ', "")
+                            }
+                        },
+                    })
+                }
+                _ => None,
+            })
+            .collect();
+        let args_struct = file.items.iter().find_map(|item| match item {
+            Item::Struct(item_struct) if item_struct.ident == exec_args_ident => {
+                Some(ArgsStructSummary {
+                    fields_doc: args_fields_doc(item_struct, file),
+                    examples_doc: args_examples_doc(item_struct),
+                    traits: derived_traits(&item_struct.attrs)
+                        .into_iter()
+                        .chain(implemented_traits(file, item_struct))
+                        .collect(),
+                })
+            }
+            _ => None,
+        });
+        Self {
+            exec_fns,
+            args_struct,
+            read_helper_attrs: read_helper_attrs(file),
+        }
+    }
+
+    /// The `exec` function named `name`
+    pub fn exec_fn(&self, name: &str) -> Option<&ExecFnSummary> {
+        self.exec_fns.iter().find(|exec_fn| exec_fn.name == name)
+    }
+
+    /// Summarize the implementation file at `path`, cached by path and modification time across invocations
+    /// # Errors
+    /// If the file can not be read or parsed, formatted
+    pub fn load(path: &Path, exec_args_ident: &str) -> std::result::Result<Arc<Self>, String> {
+        let read_error = |e: std::io::Error| format!("Failed to read {}: {e}", path.display());
+        let modified = std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(read_error)?;
+        let key = (path.to_path_buf(), exec_args_ident.to_string());
+        if let Some((cached_modified, summary)) = cache().get(&key) {
+            if *cached_modified == modified {
+                return Ok(summary.clone());
+            }
+        }
+        let src = std::fs::read_to_string(path).map_err(read_error)?;
+        let summary = Arc::new(
+            Self::parse(&src, exec_args_ident)
+                .map_err(|e| format!("Failed to parse {}: {e}", path.display()))?,
+        );
+        cache().insert(key, (modified, summary.clone()));
+        Ok(summary)
+    }
+
+    /// The summary embedded at build time with [`embed_impl_files`] for the `exec_args_ident` arguments struct,
+    /// [`None`] if not embedded
+    /// # Errors
+    /// If the embedded summary is malformed
+    pub fn embedded(
+        impls_mod: &str,
+        name: &str,
+        exec_args_ident: &str,
+    ) -> Option<serde_json::Result<Self>> {
+        let json = std::env::var(Self::env_var(impls_mod, name, exec_args_ident)).ok()?;
+        Some(serde_json::from_str(&json))
+    }
+
+    /// The env var embedding the summary of the `<impls_mod>/<name>.rs` implementation file,
+    /// read for the `exec_args_ident` arguments struct.
+    ///
+    /// Each part is hex encoded, so that distinct parts never give the same env var:
+    /// ```
+    /// use rsmack_utils::megamac::impl_file::ImplFileSummary;
+    /// assert_eq!(ImplFileSummary::env_var("impls", "a", "Args"), "RSMACK_IMPL_696D706C73_61_41726773");
+    /// assert_ne!(ImplFileSummary::env_var("a_b", "c", "Args"), ImplFileSummary::env_var("a", "b_c", "Args"));
+    /// assert_ne!(ImplFileSummary::env_var("impls", "a", "Args"), ImplFileSummary::env_var("impls", "a", "ARGS"));
+    /// ```
+    pub fn env_var(impls_mod: &str, name: &str, exec_args_ident: &str) -> String {
+        let hex = |part: &str| {
+            part.bytes().fold(String::new(), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02X}");
+                hex
+            })
+        };
+        format!(
+            "RSMACK_IMPL_{}_{}_{}",
+            hex(impls_mod),
+            hex(name),
+            hex(exec_args_ident)
+        )
+    }
+}
+
+/// The last path segment of the traits the file implements for the struct
+fn implemented_traits(file: &File, item_struct: &ItemStruct) -> Vec<String> {
+    file.items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Impl(ItemImpl {
+                trait_: Some((None, trait_path, _)),
+                self_ty,
+                ..
+            }) if matches!(&**self_ty, Type::Path(TypePath { path, .. }) if path.is_ident(&item_struct.ident)) => {
+                trait_path.segments.last().map(|s| s.ident.to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The helper attributes read by a `#[darling(attributes(..))]` of a struct or enum of the file
+fn read_helper_attrs(file: &File) -> Vec<String> {
+    let mut read_attrs = vec![];
+    let item_attrs = file.items.iter().flat_map(|item| match item {
+        Item::Struct(ItemStruct { attrs, .. }) | Item::Enum(ItemEnum { attrs, .. }) => {
+            attrs.as_slice()
+        }
+        _ => &[],
+    });
+    for attr in item_attrs.filter(|a| a.path().is_ident("darling")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("attributes") {
+                meta.parse_nested_meta(|helper| {
+                    read_attrs.extend(helper.path.get_ident().map(ToString::to_string));
+                    Ok(())
+                })
+            } else {
+                skip_nested_meta(&meta)
+            }
+        });
+    }
+    read_attrs
+}
+
+/// Embed the summaries of the implementation files of a package, **to call from its build script**.
+///
+/// Each summary is set as a [`ImplFileSummary::env_var`] env var of the package compilation,
+/// so that megamac does not read the implementation files at expansion, e.g. under rust-analyzer.
+/// Macros declared with another `args` struct than the embedded one still read their file.
+/// Files which can not be parsed are skipped with a cargo warning, megamac reporting the error.
+/// ```ignore
+/// // build.rs
+/// fn main() {
+///     rsmack_utils::megamac::impl_file::embed_impl_files().call();
+/// }
+/// ```
+/// # Panics
+/// If the implementations folder can not be read
+#[builder]
+pub fn embed_impl_files(
+    /// The module of the crate root holding the implementation modules
+    #[builder(default = "impls")]
+    impls_mod: &str,
+    /// The name of the arguments struct of the implementation modules
+    #[builder(default = "Args")]
+    args: &str,
+) {
+    let impls_folder = package_src_folder().join(impls_mod);
+    println!("cargo:rerun-if-changed={}", impls_folder.display());
+    for module in folder_modules(&impls_folder) {
+        let path = impls_folder.join(format!("{module}.rs"));
+        println!("cargo:rerun-if-changed={}", path.display());
+        let summary = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|src| ImplFileSummary::parse(&src, args).map_err(|e| e.to_string()));
+        match summary {
+            Ok(summary) => println!(
+                "cargo:rustc-env={}={}",
+                ImplFileSummary::env_var(impls_mod, &module, args),
+                serde_json::to_string(&summary).unwrap()
+            ),
+            Err(e) => println!("cargo:warning=Skipping `{module}` implementation summary, {e}"),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Machine-readable manifest of a macro declared with [rsmack-megamac](../../../rsmack_megamac/index.html)
+use std::path::PathBuf;
+
+use bon::Builder;
+use serde::Serialize;
+
+use super::args_doc::FieldDoc;
+use crate::fs::target_folder;
+
+/// Manifest of a generated macro, serialized as JSON
+#[derive(Debug, Builder, Serialize)]
+#[builder(on(String, into))]
+pub struct MacroManifest {
+    /// The name of the package exposing the macro
+    #[builder(default = std::env::var("CARGO_PKG_NAME").unwrap_or_default())]
+    pub package: String,
+    /// The macro name
+    pub name: String,
+    /// The macro kind, `Func`, `Attr` or `Derive`
+    pub kind: String,
+    /// The item types the attribute macro is applied to, several if dispatched as a `syn::Item`
+    #[builder(default)]
+    pub receivers: Vec<String>,
+    /// The macro parameters
+    #[builder(default)]
+    pub parameters: Vec<ParameterManifest>,
+}
+
+/// Manifest of a macro parameter, built from a [`FieldDoc`]
+#[derive(Debug, Serialize)]
+pub struct ParameterManifest {
+    /// The parameter name, as given to the macro
+    pub name: String,
+    /// The `Args` field name
+    pub field: String,
+    /// The parameter type
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// The parameter documentation
+    pub doc: Option<String>,
+    /// Whether the parameter may be omitted
+    pub optional: bool,
+    /// The default value expression
+    pub default: Option<String>,
+    /// Whether the parameter may be repeated
+    pub multiple: bool,
+    /// The values allowed for an enum parameter, as written in the macro input
+    pub allowed_values: Vec<String>,
+    /// The parameters of a struct parameter
+    pub parameters: Vec<ParameterManifest>,
+}
+
+impl From<&FieldDoc> for ParameterManifest {
+    fn from(fd: &FieldDoc) -> Self {
+        Self {
+            name: fd.name.clone(),
+            field: fd.ident.clone(),
+            ty: fd.ty.clone(),
+            doc: fd.doc.clone(),
+            optional: fd.optional,
+            default: fd.default.clone(),
+            multiple: fd.multiple,
+            allowed_values: fd.allowed_values.clone(),
+            parameters: fd.nested.iter().map(Self::from).collect(),
+        }
+    }
+}
+
+impl MacroManifest {
+    /// The folder holding the manifests, `$OUT_DIR/rsmack` if the package has a build script,
+    /// `target/rsmack` otherwise (see [`target_folder`])
+    pub fn folder() -> PathBuf {
+        std::env::var("OUT_DIR")
+            .map_or_else(|_| target_folder(), PathBuf::from)
+            .join("rsmack")
+    }
+
+    /// The manifest file path, `<package>/<name>.json` in [`Self::folder`]
+    pub fn path(&self) -> PathBuf {
+        Self::folder()
+            .join(&self.package)
+            .join(format!("{}.json", self.name))
+    }
+
+    /// Write the manifest as pretty JSON to [`Self::path`], creating missing folders
+    /// # Errors
+    /// If the folders creation or the file write fail
+    pub fn write(&self) -> std::io::Result<PathBuf> {
+        let path = self.path();
+        if let Some(folder) = path.parent() {
+            std::fs::create_dir_all(folder)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+}
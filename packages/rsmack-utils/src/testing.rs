@@ -0,0 +1,292 @@
+#![allow(clippy::crate_in_macro_def)]
+//! Test support to call a **proc-macro** implementation `exec` function outside of a proc-macro,
+//! with [`Logr`](crate::logr::Logr) messages captured instead of emitted, and its output compared against snapshot files.
+//!
+//! Proc-macro crates only export their macros, so tests calling an implementation live in the proc-macro crate itself:
+//! ```ignore
+//! #[cfg(test)]
+//! mod tests {
+//!     use quote::quote;
+//!     #[test]
+//!     fn wraps_fields() {
+//!         rsmack_utils::testing::expand_attr!(impls, Args, wrap, syn::ItemStruct, quote!(with = Option), quote!(struct A { a: u8 }))
+//!             .assert_snapshot("wraps_fields");
+//!     }
+//! }
+//! ```
+use std::{fmt::Write, path::PathBuf};
+
+use proc_macro2::TokenStream;
+
+use crate::{
+    debug::pretty,
+    exec::exec_captured,
+    fs::package_folder,
+    logr::{LogrLevel, LogrMessage},
+    megamac::ExecEnv,
+};
+
+/// Result of an implementation call with [`expand`]
+#[derive(Debug)]
+pub struct Expansion {
+    /// The `exec` output, `None` if the input could not be parsed or the implementation aborted
+    pub output: Option<TokenStream>,
+    /// The [`Logr`](crate::logr::Logr) messages and input parsing errors
+    pub messages: Vec<LogrMessage>,
+}
+
+impl Expansion {
+    /// The messages of the given level
+    pub fn messages(&self, level: LogrLevel) -> Vec<&str> {
+        self.messages
+            .iter()
+            .filter(|m| m.level == level)
+            .map(|m| m.msg.as_str())
+            .collect()
+    }
+    /// The error messages
+    pub fn errors(&self) -> Vec<&str> {
+        self.messages(LogrLevel::Error)
+    }
+    /// The warning messages
+    pub fn warnings(&self) -> Vec<&str> {
+        self.messages(LogrLevel::Warning)
+    }
+    /// The note messages
+    pub fn notes(&self) -> Vec<&str> {
+        self.messages(LogrLevel::Note)
+    }
+    /// The output pretty-printed with [`pretty`],
+    /// followed by the messages as comments
+    pub fn pretty(&self) -> String {
+        let mut pretty = self.output.as_ref().map(pretty).unwrap_or_default();
+        for message in &self.messages {
+            let level = match message.level {
+                LogrLevel::Error => "error",
+                LogrLevel::Warning => "warning",
+                LogrLevel::Note => "note",
+            };
+            writeln!(pretty, "// {level}: {}", message.msg).unwrap();
+            for child in &message.children {
+                writeln!(pretty, "//   {}: {}", child.kind, child.msg).unwrap();
+            }
+        }
+        pretty
+    }
+    /// Compare [`Self::pretty`] with the `tests/snapshots/<name>.rs` file of the package.
+    ///
+    /// The snapshot is written instead if the `RSMACK_UPDATE_SNAPSHOTS` env var is set.
+    /// # Panics
+    /// If the snapshot differs, is missing, or can not be read or written
+    pub fn assert_snapshot(&self, name: &str) {
+        let path = snapshot_path(name);
+        let pretty = self.pretty();
+        if std::env::var_os("RSMACK_UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, pretty)
+                .unwrap_or_else(|e| panic!("Failed to write snapshot {}: {e}", path.display()));
+            return;
+        }
+        assert!(
+            path.exists(),
+            "Snapshot {} is missing, set RSMACK_UPDATE_SNAPSHOTS=1 to write it\n--- expansion\n{pretty}",
+            path.display()
+        );
+        let snapshot = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("Failed to read snapshot {}: {e}", path.display()));
+        assert!(
+            snapshot == pretty,
+            "Snapshot {} differs, set RSMACK_UPDATE_SNAPSHOTS=1 to update it\n--- snapshot\n{snapshot}\n--- expansion\n{pretty}",
+            path.display()
+        );
+    }
+}
+
+/// The `tests/snapshots/<name>.rs` file of the package
+fn snapshot_path(name: &str) -> PathBuf {
+    package_folder()
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{name}.rs"))
+}
+
+/// Call `exec` with a capturing [`Logr`](crate::logr::Logr) environment,
+/// input parsing errors being recorded as error messages and aborts being caught
+/// # Panics
+/// Resumes panics other than [`Logr`](crate::logr::Logr) aborts
+pub fn expand<F>(env: ExecEnv, exec: F) -> Expansion
+where
+    F: FnOnce(ExecEnv) -> darling::Result<TokenStream>,
+{
+    let (output, mut messages) = exec_captured(env, exec);
+    let output = match output {
+        Some(Ok(output)) => Some(output),
+        Some(Err(errors)) => {
+            messages.extend(errors.into_iter().map(|e| LogrMessage {
+                level: LogrLevel::Error,
+                span: e.span(),
+                msg: e.to_string(),
+                children: vec![],
+            }));
+            None
+        }
+        None => None,
+    };
+    Expansion { output, messages }
+}
+
+/// Convert the output of an implementation with the given error strategy (see [`call_attr_proc_macro`](crate::exec::call_attr_proc_macro))
+/// into the result expected by [`expand`]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! exec_result {
+    ($(proc_macro_error2)?; $output:expr) => {
+        Ok($output.into())
+    };
+    (syn; $output:expr) => {
+        Ok($output?)
+    };
+}
+
+/// Build the [`ExecEnv`] of an implementation test, prefixed with the calling crate name,
+/// as [`build_env`](crate::build_env) does for the macro
+#[macro_export]
+macro_rules! test_env {
+    (
+        $implementations_mod_ident:ident,
+        $exec_args_ident:ident,
+        $exec_fn_mod_ident:ident,
+        $kind:ident
+        $(, attr = $attr_tok_stream:expr)?
+    ) => {
+        rsmack_utils::megamac::ExecEnv::builder(
+            std::env!("CARGO_CRATE_NAME"),
+            stringify!($implementations_mod_ident),
+            stringify!($exec_args_ident),
+            stringify!($exec_fn_mod_ident),
+        )
+        .kind(rsmack_utils::megamac::MacroKind::$kind)
+        $(.attr($attr_tok_stream))?
+        .build()
+    };
+}
+
+/// Call an attribute proc-macro implementation with [`expand`], from attribute and item token streams,
+/// optionally given the `bare`, `primary`, `exec` and `errors` options of [`call_attr_proc_macro`](crate::exec::call_attr_proc_macro)
+#[macro_export]
+macro_rules! expand_attr {
+    (
+        $implementations_mod_ident:ident,
+        $exec_args_ident:ident,
+        $exec_fn_mod_ident:ident,
+        $item_ty:ty,
+        $attr_tok_stream:expr,
+        $item_tok_stream:expr
+        $(, bare = $bare:expr)?
+        $(, primary = $primary:ident)?
+        $(, exec = $exec:expr)?
+        $(, errors = $errors:ident)?
+    ) => {{
+        let attr_tok_stream: proc_macro2::TokenStream = $attr_tok_stream;
+        rsmack_utils::testing::expand(
+            rsmack_utils::testing::test_env!(
+                $implementations_mod_ident,
+                $exec_args_ident,
+                $exec_fn_mod_ident,
+                Attr,
+                attr = attr_tok_stream.clone()
+            ),
+            |env| {
+                let (parsed_args, parsed_item) = rsmack_utils::exec::parse_attr_input::<
+                    crate::$implementations_mod_ident::$exec_fn_mod_ident::$exec_args_ident,
+                    $item_ty,
+                >(
+                    attr_tok_stream,
+                    $item_tok_stream,
+                    None $(.or(Some($bare)))?,
+                    None $(.or(Some(stringify!($primary))))?,
+                )?;
+                let exec = rsmack_utils::exec::exec_fn_or!(
+                    $($exec)?;
+                    crate::$implementations_mod_ident::$exec_fn_mod_ident::exec
+                );
+                rsmack_utils::testing::exec_result!($($errors)?; exec(parsed_args, parsed_item, env))
+            },
+        )
+    }};
+}
+
+/// Call a function proc-macro implementation with [`expand`], from an arguments token stream,
+/// optionally given the `input` and `errors` options of [`call_func_proc_macro`](crate::exec::call_func_proc_macro)
+#[macro_export]
+macro_rules! expand_func {
+    (
+        $implementations_mod_ident:ident,
+        $exec_args_ident:ident,
+        $exec_fn_mod_ident:ident,
+        $args_tok_stream:expr
+        $(, input = $input:ident)?
+        $(, errors = $errors:ident)?
+    ) => {
+        rsmack_utils::testing::expand(
+            rsmack_utils::testing::test_env!(
+                $implementations_mod_ident,
+                $exec_args_ident,
+                $exec_fn_mod_ident,
+                Func
+            ),
+            |env| {
+                let parsed_args = rsmack_utils::exec::parse_func_input!(
+                    $($input)?;
+                    crate::$implementations_mod_ident::$exec_fn_mod_ident::$exec_args_ident,
+                    $args_tok_stream
+                )?;
+                rsmack_utils::testing::exec_result!(
+                    $($errors)?;
+                    crate::$implementations_mod_ident::$exec_fn_mod_ident::exec(parsed_args, env)
+                )
+            },
+        )
+    };
+}
+
+/// Call a derive proc-macro implementation with [`expand`], from an item token stream,
+/// optionally given the `errors` option of [`call_derive_proc_macro`](crate::exec::call_derive_proc_macro)
+#[macro_export]
+macro_rules! expand_derive {
+    (
+        $implementations_mod_ident:ident,
+        $exec_args_ident:ident,
+        $exec_fn_mod_ident:ident,
+        $item_tok_stream:expr
+        $(, errors = $errors:ident)?
+    ) => {
+        rsmack_utils::testing::expand(
+            rsmack_utils::testing::test_env!(
+                $implementations_mod_ident,
+                $exec_args_ident,
+                $exec_fn_mod_ident,
+                Derive
+            ),
+            |env| {
+                use darling::*;
+                let parsed_item = syn::parse2::<syn::DeriveInput>($item_tok_stream)?;
+                let parsed_args =
+                    crate::$implementations_mod_ident::$exec_fn_mod_ident::$exec_args_ident::from_derive_input(
+                        &parsed_item,
+                    )?;
+                rsmack_utils::testing::exec_result!(
+                    $($errors)?;
+                    crate::$implementations_mod_ident::$exec_fn_mod_ident::exec(parsed_args, env)
+                )
+            },
+        )
+    };
+}
+
+#[doc(hidden)]
+pub use exec_result;
+pub use expand_attr;
+pub use expand_derive;
+pub use expand_func;
+pub use test_env;
@@ -0,0 +1,19 @@
+use rsmack_wrap::wrap;
+
+#[wrap(with = Option)]
+#[derive(Debug, Default)]
+struct Config {
+    name: String,
+    retries: u8,
+    tags: [String; 2],
+}
+
+fn main() {
+    let config = Config {
+        retries: Some(3),
+        ..Default::default()
+    };
+    assert_eq!(config.name, None);
+    assert_eq!(config.retries, Some(3));
+    assert_eq!(config.tags, None);
+}
@@ -1,6 +1,5 @@
 use darling::FromMeta;
 use proc_macro2::TokenStream;
-use proc_macro_error2::*;
 use quote::{quote, ToTokens};
 use rsmack_utils::megamac::ExecEnv;
 use syn::spanned::Spanned;
@@ -30,14 +29,20 @@ pub fn exec(args: Args, item: ItemStruct, env: ExecEnv) -> TokenStream {
                         Type::Array(t) => wrap_field_ty(wrapper_ty, t, f),
                         _ => {
                             let ty = f.ty.clone();
-                            let ty_span = ty.span().unwrap();
-                            emit_error!(
-                                ty_span.clone(),
-                                format!(
-                                    "#[rsmack_wrap::wrap]: Field type not supported {}",
-                                    format!("{ty:?}").split(' ').next().unwrap()
+                            env.logr
+                                .error(
+                                    ty.span(),
+                                    format!(
+                                        "Field type not supported {}",
+                                        format!("{ty:?}").split(' ').next().unwrap()
+                                    ),
                                 )
-                            );
+                                .help("only path, slice, tuple and array types are wrapped")
+                                .note(format!(
+                                    "the field is left as is, wrap it by hand with `{}<...>`",
+                                    args.with
+                                ))
+                                .emit();
                             Field {
                                 ty: ty.clone(),
                                 attrs: f.attrs.clone(),
@@ -55,10 +60,14 @@ pub fn exec(args: Args, item: ItemStruct, env: ExecEnv) -> TokenStream {
                 named: transformed_fields_named,
             })
         }
-        _ => env.logr.abort(
-            transformed_item.fields.span(),
-            "Only named struct supported",
-        ),
+        _ => env
+            .logr
+            .error(
+                transformed_item.fields.span(),
+                "Only named struct supported",
+            )
+            .help("use named fields, e.g. `struct Config { name: String }`")
+            .abort(),
     };
     quote! {
         #transformed_item
@@ -79,3 +88,102 @@ fn wrap_field_ty(wrapper: impl ToTokens, inner_ty: impl ToTokens, f: Field) -> F
         colon_token: f.colon_token,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use rsmack_utils::testing::expand_attr;
+
+    #[test]
+    fn wraps_named_fields() {
+        expand_attr!(
+            impls,
+            Args,
+            wrap,
+            syn::ItemStruct,
+            quote!(with = Option),
+            quote! {
+                struct Config {
+                    name: String,
+                    tags: [String; 2],
+                    pair: (u8, u8),
+                    refs: &'static str,
+                }
+            }
+        )
+        .assert_snapshot("wrap_named_fields");
+    }
+
+    #[test]
+    fn wraps_with_primary_shorthand() {
+        let expansion = expand_attr!(
+            impls,
+            Args,
+            wrap,
+            syn::ItemStruct,
+            quote!(Option),
+            quote!(
+                struct Config {
+                    name: String,
+                }
+            ),
+            primary = with
+        );
+        assert!(expansion.errors().is_empty());
+        expansion.assert_snapshot("wrap_primary_shorthand");
+    }
+
+    #[test]
+    fn aborts_on_tuple_struct() {
+        let expansion = expand_attr!(
+            impls,
+            Args,
+            wrap,
+            syn::ItemStruct,
+            quote!(with = Option),
+            quote!(
+                struct Config(String);
+            )
+        );
+        assert!(expansion.output.is_none());
+        assert_eq!(
+            expansion.errors(),
+            ["#[rsmack_wrap::wrap] Only named struct supported"]
+        );
+    }
+
+    #[test]
+    fn reports_missing_with() {
+        let expansion = expand_attr!(
+            impls,
+            Args,
+            wrap,
+            syn::ItemStruct,
+            quote!(),
+            quote!(
+                struct Config {
+                    name: String,
+                }
+            )
+        );
+        assert_eq!(expansion.errors(), ["Missing field `with`"]);
+    }
+
+    #[test]
+    fn reports_args_and_item_errors_together() {
+        let expansion = expand_attr!(
+            impls,
+            Args,
+            wrap,
+            syn::ItemStruct,
+            quote!(),
+            quote!(
+                enum Config {}
+            )
+        );
+        assert_eq!(
+            expansion.errors(),
+            ["Missing field `with`", "expected `struct`"]
+        );
+    }
+}
@@ -1,3 +1,8 @@
 use rsmack_megamac::*;
 mod impls;
-megamac!(kind = Attr, name = wrap, receiver = ItemStruct);
+megamac!(
+    kind = Attr,
+    name = wrap,
+    receiver = ItemStruct,
+    primary = with
+);
@@ -0,0 +1,9 @@
+struct Config {
+    name: Option<String>,
+    tags: Option<[String; 2]>,
+    pair: Option<(u8, u8)>,
+    refs: &'static str,
+}
+// error: #[rsmack_wrap::wrap] Field type not supported Type::Reference
+//   help: only path, slice, tuple and array types are wrapped
+//   note: the field is left as is, wrap it by hand with `Option<...>`
@@ -0,0 +1,3 @@
+struct Config {
+    name: Option<String>,
+}